@@ -0,0 +1,321 @@
+/*!
+# Register access
+
+The 8250 register logic in [`Uart8250`](crate::Uart8250) is written against [`RegisterAccess`],
+so the same driver can talk to a memory-mapped UART or to a legacy PC COM port.
+
+Memory-mapped registers can be spread out and wider than a byte, see [`RegisterLayout`].
+*/
+
+use core::marker::PhantomData;
+use core::ptr;
+#[cfg(target_has_atomic = "8")]
+use core::sync::atomic::{AtomicBool, Ordering};
+
+/// A way to read and write the registers of an 8250 UART.
+///
+/// `offset` is the register index (see [`registers::offset`](crate::registers::offset)), not a byte offset.
+pub trait RegisterAccess {
+    /// Read the register at `offset`
+    fn read(&self, offset: usize) -> u8;
+
+    /// Write `value` to the register at `offset`
+    fn write(&self, offset: usize, value: u8);
+
+    /// Read the register at `offset`, apply `f` and write the result back
+    #[inline]
+    fn modify<F: FnOnce(u8) -> u8>(&self, offset: usize, f: F) {
+        self.write(offset, f(self.read(offset)))
+    }
+}
+
+impl<T: RegisterAccess + ?Sized> RegisterAccess for &T {
+    #[inline]
+    fn read(&self, offset: usize) -> u8 {
+        (**self).read(offset)
+    }
+
+    #[inline]
+    fn write(&self, offset: usize, value: u8) {
+        (**self).write(offset, value)
+    }
+}
+
+/// Width of a single register access
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum RegisterWidth {
+    U8,
+    U16,
+    U32,
+}
+
+/// Placement of memory-mapped registers, the equivalent of the Linux device tree properties
+/// `reg-shift`, `reg-io-width` and `big-endian`.
+pub trait RegisterLayout {
+    /// Registers are `1 << reg_shift` bytes apart
+    fn reg_shift(&self) -> u32;
+
+    /// Width used to access each register
+    fn reg_io_width(&self) -> RegisterWidth;
+
+    /// Whether registers wider than a byte are big-endian
+    fn is_big_endian(&self) -> bool;
+}
+
+/// The classic layout: byte registers at consecutive addresses
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub struct DefaultLayout;
+
+impl RegisterLayout for DefaultLayout {
+    #[inline]
+    fn reg_shift(&self) -> u32 {
+        0
+    }
+
+    #[inline]
+    fn reg_io_width(&self) -> RegisterWidth {
+        RegisterWidth::U8
+    }
+
+    #[inline]
+    fn is_big_endian(&self) -> bool {
+        false
+    }
+}
+
+/// A layout chosen at runtime, e.g. from the device tree
+///
+/// Most SoC UARTs (DesignWare APB, K210, Allwinner, Rockchip...) use
+/// `reg_shift: 2, reg_io_width: RegisterWidth::U32`.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct MmioLayout {
+    pub reg_shift: u32,
+    pub reg_io_width: RegisterWidth,
+    pub big_endian: bool,
+}
+
+impl Default for MmioLayout {
+    fn default() -> Self {
+        Self {
+            reg_shift: 0,
+            reg_io_width: RegisterWidth::U8,
+            big_endian: false,
+        }
+    }
+}
+
+impl RegisterLayout for MmioLayout {
+    #[inline]
+    fn reg_shift(&self) -> u32 {
+        self.reg_shift
+    }
+
+    #[inline]
+    fn reg_io_width(&self) -> RegisterWidth {
+        self.reg_io_width
+    }
+
+    #[inline]
+    fn is_big_endian(&self) -> bool {
+        self.big_endian
+    }
+}
+
+/// Memory-mapped registers
+pub struct Mmio<'a, L: RegisterLayout = DefaultLayout> {
+    base: *mut u8,
+    layout: L,
+    _marker: PhantomData<&'a mut u8>,
+}
+
+// `Mmio::new` requires exclusive access to the registers, so moving it to another thread is fine.
+unsafe impl<L: RegisterLayout + Send> Send for Mmio<'_, L> {}
+
+impl<'a, L: RegisterLayout> Mmio<'a, L> {
+    /// Access the registers starting at the given base address.
+    ///
+    /// # Safety
+    ///
+    /// - `base_address` must be the base address of the registers of an 8250 compatible UART,
+    ///   placed as described by `layout` and mapped as device memory for at least `'a`.
+    /// - Nothing else may access these registers while the returned value exists,
+    ///   including another `Mmio` created for the same address.
+    pub const unsafe fn new(base_address: usize, layout: L) -> Self {
+        Self {
+            base: base_address as *mut u8,
+            layout,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Get the register layout
+    pub fn layout(&self) -> &L {
+        &self.layout
+    }
+
+    #[inline]
+    fn address(&self, offset: usize) -> *mut u8 {
+        self.base.wrapping_add(offset << self.layout.reg_shift())
+    }
+}
+
+impl<L: RegisterLayout> RegisterAccess for Mmio<'_, L> {
+    #[inline]
+    fn read(&self, offset: usize) -> u8 {
+        let address = self.address(offset);
+        let big_endian = self.layout.is_big_endian();
+        unsafe {
+            match self.layout.reg_io_width() {
+                RegisterWidth::U8 => ptr::read_volatile(address),
+                RegisterWidth::U16 => {
+                    let value = ptr::read_volatile(address as *const u16);
+                    (if big_endian {
+                        u16::from_be(value)
+                    } else {
+                        u16::from_le(value)
+                    }) as u8
+                }
+                RegisterWidth::U32 => {
+                    let value = ptr::read_volatile(address as *const u32);
+                    (if big_endian {
+                        u32::from_be(value)
+                    } else {
+                        u32::from_le(value)
+                    }) as u8
+                }
+            }
+        }
+    }
+
+    #[inline]
+    fn write(&self, offset: usize, value: u8) {
+        let address = self.address(offset);
+        let big_endian = self.layout.is_big_endian();
+        unsafe {
+            match self.layout.reg_io_width() {
+                RegisterWidth::U8 => ptr::write_volatile(address, value),
+                RegisterWidth::U16 => {
+                    let value = value as u16;
+                    ptr::write_volatile(
+                        address as *mut u16,
+                        if big_endian {
+                            value.to_be()
+                        } else {
+                            value.to_le()
+                        },
+                    )
+                }
+                RegisterWidth::U32 => {
+                    let value = value as u32;
+                    ptr::write_volatile(
+                        address as *mut u32,
+                        if big_endian {
+                            value.to_be()
+                        } else {
+                            value.to_le()
+                        },
+                    )
+                }
+            }
+        }
+    }
+}
+
+/// A memory-mapped device which can be taken only once
+///
+/// Meant to be kept in a `static`, one for each device, so that safe code can get
+/// exactly one owned handle per device.
+#[cfg(target_has_atomic = "8")]
+pub struct MmioDevice {
+    base_address: usize,
+    taken: AtomicBool,
+}
+
+#[cfg(target_has_atomic = "8")]
+impl MmioDevice {
+    /// Describe the device at the given base address.
+    ///
+    /// # Safety
+    ///
+    /// - `base_address` must be the base address of the device registers, mapped as device memory
+    ///   for the rest of the program.
+    /// - This must be the only `MmioDevice` for the device, and the registers must not be accessed
+    ///   other than through the handle taken from it.
+    pub const unsafe fn new(base_address: usize) -> Self {
+        Self {
+            base_address,
+            taken: AtomicBool::new(false),
+        }
+    }
+
+    /// Get the base address the first time, `None` afterwards
+    ///
+    /// The address can only be turned into a driver with `unsafe`; use the `take` constructors instead.
+    pub fn claim(&self) -> Option<usize> {
+        if self.taken.swap(true, Ordering::AcqRel) {
+            None
+        } else {
+            Some(self.base_address)
+        }
+    }
+
+    /// Get the base address, whether or not the device has been taken
+    pub const fn base_address(&self) -> usize {
+        self.base_address
+    }
+}
+
+/// x86 I/O port registers, as used by the PC COM ports (0x3F8, 0x2F8, 0x3E8, 0x2E8)
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct Port {
+    base: u16,
+}
+
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+impl Port {
+    /// Access the registers starting at the given I/O port.
+    ///
+    /// # Safety
+    ///
+    /// - `base` must be the first I/O port of an 8250 compatible UART, and the code must be
+    ///   allowed to use `in` and `out` on it.
+    /// - Nothing else may access these ports while the returned value exists.
+    pub const unsafe fn new(base: u16) -> Self {
+        Self { base }
+    }
+
+    /// Get the base I/O port
+    pub const fn base(&self) -> u16 {
+        self.base
+    }
+}
+
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+impl RegisterAccess for Port {
+    #[inline]
+    fn read(&self, offset: usize) -> u8 {
+        let value: u8;
+        unsafe {
+            core::arch::asm!(
+                "in al, dx",
+                out("al") value,
+                in("dx") self.base + offset as u16,
+                options(nomem, nostack, preserves_flags),
+            );
+        }
+        value
+    }
+
+    #[inline]
+    fn write(&self, offset: usize, value: u8) {
+        unsafe {
+            core::arch::asm!(
+                "out dx, al",
+                in("dx") self.base + offset as u16,
+                in("al") value,
+                options(nomem, nostack, preserves_flags),
+            );
+        }
+    }
+}
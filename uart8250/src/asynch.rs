@@ -0,0 +1,268 @@
+/*!
+# Async driver
+
+[`AsyncUart8250`] implements `embedded_io_async::Read` and `Write`. It is meant to live in a `static`
+shared by the task and the interrupt handler, which calls [`AsyncUart8250::handle_interrupt`]:
+
+```no_run
+use uart8250::asynch::AsyncUart8250;
+use uart8250::access::{DefaultLayout, Mmio};
+use uart8250::MmioUart8250;
+
+// SAFETY: 0x1000_0000 is the UART, and it is not used anywhere else.
+static UART: AsyncUart8250<Mmio<'static>> =
+    AsyncUart8250::new(unsafe { MmioUart8250::with_layout(0x1000_0000, DefaultLayout) });
+
+fn uart_interrupt_handler() {
+    UART.handle_interrupt();
+}
+
+async fn echo() {
+    use embedded_io_async::{Read, Write};
+
+    UART.init(11_059_200, 115200);
+    let mut uart = &UART;
+    let mut buf = [0; 16];
+    loop {
+        if let Ok(count) = uart.read(&mut buf).await {
+            uart.write_all(&buf[..count]).await.ok();
+        }
+    }
+}
+```
+
+Interrupt sources are only enabled while a task waits on them. The interrupt handler masks the source
+which fired and wakes the waiting task, so a level triggered interrupt can't storm.
+Every register access is made in a critical section, including those through
+[`AsyncUart8250::with_uart`].
+*/
+
+use core::cell::RefCell;
+use core::future::poll_fn;
+use core::task::{Poll, Waker};
+
+use critical_section::Mutex;
+
+use crate::access::RegisterAccess;
+use crate::{InterruptType, LineError, Uart8250, IER, LSR};
+
+/// Bound on the number of interrupt causes handled in one call, in case the hardware misbehaves
+const MAX_INTERRUPT_CAUSES: usize = 8;
+
+/// A [`Waker`] shared between a task and an interrupt handler
+pub struct WakerSlot {
+    waker: Mutex<RefCell<Option<Waker>>>,
+}
+
+impl WakerSlot {
+    pub const fn new() -> Self {
+        Self {
+            waker: Mutex::new(RefCell::new(None)),
+        }
+    }
+
+    /// Register `waker` to be woken, replacing the previous one
+    pub fn register(&self, waker: &Waker) {
+        critical_section::with(|cs| {
+            let mut slot = self.waker.borrow_ref_mut(cs);
+            match slot.as_ref() {
+                Some(old) if old.will_wake(waker) => {}
+                _ => *slot = Some(waker.clone()),
+            }
+        })
+    }
+
+    /// Wake and forget the registered waker, if any
+    pub fn wake(&self) {
+        if let Some(waker) = critical_section::with(|cs| self.waker.borrow_ref_mut(cs).take()) {
+            waker.wake();
+        }
+    }
+}
+
+impl Default for WakerSlot {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// # Interrupt driven async 8250 UART
+///
+/// See the [module documentation](self) for how to share it with the interrupt handler.
+pub struct AsyncUart8250<A: RegisterAccess> {
+    uart: Uart8250<A>,
+    rx_waker: WakerSlot,
+    tx_waker: WakerSlot,
+}
+
+// Like `critical_section::Mutex`: the UART is only reached in critical sections, so only one
+// thread uses it at a time, which `Send` allows.
+unsafe impl<A: RegisterAccess + Send> Sync for AsyncUart8250<A> {}
+
+impl<A: RegisterAccess> AsyncUart8250<A> {
+    pub const fn new(uart: Uart8250<A>) -> Self {
+        Self {
+            uart,
+            rx_waker: WakerSlot::new(),
+            tx_waker: WakerSlot::new(),
+        }
+    }
+
+    /// Initialises the UART like [`Uart8250::init`], but with all interrupts disabled until a task waits.
+    pub fn init(&self, clock: usize, baud_rate: usize) {
+        self.with_uart(|uart| {
+            uart.init(clock, baud_rate);
+            uart.set_ier(IER::empty());
+        })
+    }
+
+    /// Calls `f` with the underlying UART in a critical section, returning what it returns
+    pub fn with_uart<R>(&self, f: impl FnOnce(&Uart8250<A>) -> R) -> R {
+        critical_section::with(|_| f(&self.uart))
+    }
+
+    /// Services the UART interrupt: masks each pending source and wakes the task waiting on it.
+    pub fn handle_interrupt(&self) {
+        let (wake_rx, wake_tx) = self.with_uart(|uart| {
+            let (mut wake_rx, mut wake_tx) = (false, false);
+            for _ in 0..MAX_INTERRUPT_CAUSES {
+                match uart.read_interrupt_type() {
+                    Some(InterruptType::ReceiverLineStatus)
+                    | Some(InterruptType::ReceivedDataAvailable)
+                    | Some(InterruptType::Timeout) => {
+                        self.disable_interrupts(IER::RDAI | IER::RLSI);
+                        wake_rx = true;
+                    }
+                    // Reading IIR has cleared it
+                    Some(InterruptType::TransmitterHoldingRegisterEmpty) => {
+                        self.disable_interrupts(IER::THREI);
+                        wake_tx = true;
+                    }
+                    Some(InterruptType::ModemStatus) => {
+                        uart.read_msr();
+                    }
+                    Some(InterruptType::Reserved) | None => break,
+                }
+            }
+            (wake_rx, wake_tx)
+        });
+        if wake_rx {
+            self.rx_waker.wake();
+        }
+        if wake_tx {
+            self.tx_waker.wake();
+        }
+    }
+
+    fn enable_interrupts(&self, flags: IER) {
+        self.with_uart(|uart| uart.modify_ier(|ier| ier | flags));
+    }
+
+    fn disable_interrupts(&self, flags: IER) {
+        self.with_uart(|uart| uart.modify_ier(|ier| ier & !flags));
+    }
+
+    /// Read the characters which are ready, without waiting
+    fn read_ready_bytes(&self, buf: &mut [u8]) -> Result<usize, LineError> {
+        self.with_uart(|uart| {
+            let mut count = 0;
+            while count < buf.len() {
+                let lsr = uart.raw_lsr() | uart.take_rx_errors();
+                if let Some(error) = LineError::from_lsr(lsr) {
+                    if count == 0 {
+                        return Err(error);
+                    }
+                    uart.keep_rx_errors(lsr);
+                    break;
+                } else if lsr.contains(LSR::DR) {
+                    buf[count] = uart.read_rbr();
+                    count += 1;
+                } else {
+                    break;
+                }
+            }
+            Ok(count)
+        })
+    }
+
+    /// Wait for at least one character, then read as many as are ready
+    pub async fn read(&self, buf: &mut [u8]) -> Result<usize, LineError> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        poll_fn(|cx| {
+            self.rx_waker.register(cx.waker());
+            match self.read_ready_bytes(buf) {
+                Ok(0) => {
+                    self.enable_interrupts(IER::RDAI | IER::RLSI);
+                    Poll::Pending
+                }
+                result => Poll::Ready(result),
+            }
+        })
+        .await
+    }
+
+    /// Wait for THR to be empty, then write as many bytes as it takes
+    ///
+    /// LSR error bits read while waiting are kept for the next read.
+    pub async fn write(&self, buf: &[u8]) -> Result<usize, LineError> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        poll_fn(|cx| {
+            self.tx_waker.register(cx.waker());
+            let count = self.with_uart(|uart| {
+                let mut count = 0;
+                while count < buf.len() && uart.tx_lsr().contains(LSR::THRE) {
+                    uart.write_thr(buf[count]);
+                    count += 1;
+                }
+                count
+            });
+            if count > 0 {
+                Poll::Ready(Ok(count))
+            } else {
+                self.enable_interrupts(IER::THREI);
+                Poll::Pending
+            }
+        })
+        .await
+    }
+
+    /// Wait for the transmitter to be empty
+    ///
+    /// There is no interrupt for the shift register becoming empty, so this wakes on THRE and polls.
+    pub async fn flush(&self) -> Result<(), LineError> {
+        poll_fn(|cx| {
+            self.tx_waker.register(cx.waker());
+            if self.with_uart(|uart| uart.tx_lsr().contains(LSR::DHRE)) {
+                Poll::Ready(Ok(()))
+            } else {
+                self.enable_interrupts(IER::THREI);
+                Poll::Pending
+            }
+        })
+        .await
+    }
+}
+
+impl<A: RegisterAccess> embedded_io_async::ErrorType for &AsyncUart8250<A> {
+    type Error = LineError;
+}
+
+impl<A: RegisterAccess> embedded_io_async::Read for &AsyncUart8250<A> {
+    async fn read(&mut self, buf: &mut [u8]) -> Result<usize, Self::Error> {
+        AsyncUart8250::read(self, buf).await
+    }
+}
+
+impl<A: RegisterAccess> embedded_io_async::Write for &AsyncUart8250<A> {
+    async fn write(&mut self, buf: &[u8]) -> Result<usize, Self::Error> {
+        AsyncUart8250::write(self, buf).await
+    }
+
+    async fn flush(&mut self) -> Result<(), Self::Error> {
+        AsyncUart8250::flush(self).await
+    }
+}
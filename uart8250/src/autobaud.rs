@@ -0,0 +1,143 @@
+/*!
+# Baud rate detection
+
+The 8250 can't time the incoming line itself, so [`Uart8250::autobaud`] tries each candidate baud
+rate in turn and scores the characters received at it. At the wrong rate the sender's characters
+come in garbled, usually with framing errors.
+
+The sender should repeat a known sync character, such as `0x55` (`U`, which toggles on every bit)
+or a carriage return from someone pressing enter. Without a sync character, only the line errors
+are scored, which needs parity to be enabled to tell apart nearby rates.
+
+```no_run
+use uart8250::autobaud::{AutobaudConfig, AutobaudMode};
+use uart8250::MmioUart8250;
+
+let uart: MmioUart8250 = unsafe { MmioUart8250::new(0x1000_0000) };
+uart.init(1_843_200, 115200);
+
+// Press enter a few times on the other end
+let plan = uart
+    .autobaud(1_843_200, &AutobaudConfig::new(Some(b'\r')), AutobaudMode::Program)
+    .unwrap();
+assert_eq!(uart.read_baud_plan(1_843_200).divisor, plan.divisor);
+```
+*/
+
+use core::hint::spin_loop;
+
+use crate::access::RegisterAccess;
+use crate::{BaudPlan, Error, LineError, Uart8250, LSR};
+
+/// FIFO size of the 16750
+const MAX_FIFO_SIZE: usize = 64;
+
+/// What to do with the UART once the baud rate is found
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum AutobaudMode {
+    /// Leave the detected baud rate programmed, like [`Uart8250::set_divisor`] would
+    Program,
+    /// Only report the detected baud rate, and put the divisor back as it was
+    Passive,
+}
+
+/// # Settings for [`Uart8250::autobaud`]
+#[derive(Copy, Clone, Debug)]
+pub struct AutobaudConfig<'a> {
+    /// Baud rates to try, in order of preference
+    pub candidates: &'a [usize],
+    /// The character the sender repeats, or `None` to only score line errors
+    pub sync: Option<u8>,
+    /// Characters to receive at each candidate
+    pub samples: usize,
+    /// How many times to poll LSR while waiting for each character before moving on
+    pub polls: usize,
+    /// Tolerance for reaching a candidate with the divisor, see [`BaudPlan::within`]
+    pub tolerance_ppm: u32,
+}
+
+impl<'a> AutobaudConfig<'a> {
+    /// Try all of [`BaudPlan::STANDARD_RATES`], 4 characters each
+    pub const fn new(sync: Option<u8>) -> Self {
+        Self {
+            candidates: BaudPlan::STANDARD_RATES,
+            sync,
+            samples: 4,
+            polls: 1_000_000,
+            tolerance_ppm: 20_000,
+        }
+    }
+}
+
+impl<A: RegisterAccess> Uart8250<A> {
+    /// Detects the baud rate of the sender by trying each of `config.candidates`.
+    ///
+    /// Returns the plan of the first candidate at which every sample was received intact, or else
+    /// the best scoring one. Returns [`Error::NotDetected`] if nothing was received intact at any
+    /// candidate. Other characters received meanwhile are lost.
+    pub fn autobaud(
+        &self,
+        clock: usize,
+        config: &AutobaudConfig,
+        mode: AutobaudMode,
+    ) -> Result<BaudPlan, Error> {
+        let previous = self.read_baud_plan(clock);
+        let mut best: Option<(usize, BaudPlan)> = None;
+        for &baud_rate in config.candidates {
+            let plan = match self.try_set_divisor(clock, baud_rate, config.tolerance_ppm) {
+                Ok(plan) => plan,
+                Err(_) => continue,
+            };
+            let score = self.score_baud_rate(config);
+            if score > best.map_or(0, |(score, _)| score) {
+                best = Some((score, plan));
+                if score == config.samples {
+                    break;
+                }
+            }
+        }
+
+        let detected = best.map(|(_, plan)| plan).ok_or(Error::NotDetected);
+        match (mode, detected) {
+            (AutobaudMode::Program, Ok(plan)) => self.write_baud_plan(&plan),
+            _ => self.write_baud_plan(&previous),
+        }
+        detected
+    }
+
+    /// Count the characters received intact at the current baud rate
+    fn score_baud_rate(&self, config: &AutobaudConfig) -> usize {
+        // Drop whatever was received at the previous rate, and the errors it latched. The sender
+        // keeps sending, so this stops after a FIFO full.
+        for _ in 0..MAX_FIFO_SIZE {
+            if !self.raw_lsr().contains(LSR::DR) {
+                break;
+            }
+            self.read_rbr();
+        }
+
+        let mut intact = 0;
+        for _ in 0..config.samples {
+            let mut received = false;
+            for _ in 0..config.polls {
+                let lsr = self.raw_lsr();
+                if lsr.contains(LSR::DR) {
+                    let byte = self.read_rbr();
+                    if LineError::from_lsr(lsr).is_none()
+                        && config.sync.map_or(true, |sync| sync == byte)
+                    {
+                        intact += 1;
+                    }
+                    received = true;
+                    break;
+                }
+                spin_loop();
+            }
+            if !received {
+                // Nothing is being sent at this rate, or at all
+                break;
+            }
+        }
+        intact
+    }
+}
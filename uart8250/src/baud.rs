@@ -0,0 +1,302 @@
+use crate::Error;
+
+/// # Baud rate generator of a 16550 variant
+///
+/// Many 16550 compatible UARTs can divide the input clock more finely than DLL/DLH alone.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum DivisorVariant {
+    /// `clock / (16 * divisor)`
+    Standard,
+    /// Synopsys DesignWare APB UART with a `dlf_bits` wide Divisor Latch Fraction register
+    ///
+    /// `clock / (16 * (divisor + DLF / 2^dlf_bits))`, `dlf_bits` is `DLF_SIZE` of the IP (up to 8).
+    DesignWare { dlf_bits: u8 },
+    /// NXP LPC Fractional Divider Register
+    ///
+    /// `clock / (16 * divisor * (1 + DIVADDVAL / MULVAL))`
+    NxpLpc,
+    /// Oxford Semiconductor OX16C950 with the samples per bit in the Times Clock Register
+    ///
+    /// `clock / (TCR * divisor)`, TCR from 4 to 16
+    Ox16C950,
+    /// MediaTek high speed mode with a sample count
+    ///
+    /// `clock / (sample count * divisor)`, sample count from 2 to 256
+    MediaTek,
+}
+
+/// Variant specific part of a [`BaudPlan`]
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum DivisorExtension {
+    /// Only DLL/DLH are used
+    None,
+    /// DesignWare DLF, as a fraction of the divisor in units of `1 / 2^bits`
+    Fraction { dlf: u8, bits: u8 },
+    /// NXP LPC FDR
+    FractionalDivider { mulval: u8, divaddval: u8 },
+    /// OX16C950 TCR, as samples per bit
+    SamplesPerBit(u8),
+    /// MediaTek sample count
+    SampleCount(u16),
+}
+
+/// # Divisor for a baud rate
+///
+/// The UART divides its input clock by 16 times the divisor, so most baud rates can only be
+/// approximated. [`BaudPlan::new`] picks the divisor giving the nearest rate and reports how far
+/// off it is.
+///
+/// ```
+/// use uart8250::{BaudPlan, DivisorVariant};
+///
+/// let plan = BaudPlan::new(24_000_000, 115200).unwrap();
+/// assert_eq!(plan.divisor, 13);
+/// assert_eq!(plan.actual_baud_rate, 115385);
+/// assert_eq!(plan.error_ppm, 1602);
+///
+/// // Reject anything more than 1% off
+/// assert!(BaudPlan::with_tolerance(24_000_000, 921600, 10_000).is_err());
+///
+/// // Unless the UART can divide more finely
+/// let plan = BaudPlan::for_variant(DivisorVariant::Ox16C950, 24_000_000, 921600).unwrap();
+/// assert!(plan.error_ppm.abs() < 10_000);
+/// ```
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct BaudPlan {
+    pub divisor: u16,
+    pub extension: DivisorExtension,
+    /// The baud rate `divisor` gives, rounded to the nearest integer
+    pub actual_baud_rate: usize,
+    /// Error of the actual baud rate relative to the requested one, in parts per million
+    pub error_ppm: i32,
+}
+
+impl BaudPlan {
+    /// Commonly used baud rates, in ascending order
+    pub const STANDARD_RATES: &'static [usize] = &[
+        50, 75, 110, 134, 150, 200, 300, 600, 1200, 1800, 2400, 4800, 9600, 19200, 38400, 57600,
+        115200, 230400, 460800, 500000, 576000, 921600, 1000000, 1152000, 1500000, 2000000,
+        2500000, 3000000, 3500000, 4000000,
+    ];
+
+    /// Tolerance used by [`Uart8250::apply_line_config`](crate::Uart8250::apply_line_config)
+    ///
+    /// A receiver samples the middle of each bit, so beyond about 5% it will misread characters.
+    pub const DEFAULT_TOLERANCE_PPM: u32 = 50_000;
+
+    /// Picks the divisor giving the baud rate nearest to `baud_rate`.
+    ///
+    /// Returns [`Error::InvalidConfig`] if `baud_rate` is 0, or [`Error::UnsupportedDivisor`] if
+    /// the divisor doesn't fit in DLL/DLH.
+    pub fn new(clock: usize, baud_rate: usize) -> Result<Self, Error> {
+        Self::for_variant(DivisorVariant::Standard, clock, baud_rate)
+    }
+
+    /// Picks the divisor and variant specific setting giving the baud rate nearest to `baud_rate`.
+    pub fn for_variant(
+        variant: DivisorVariant,
+        clock: usize,
+        baud_rate: usize,
+    ) -> Result<Self, Error> {
+        if baud_rate == 0 {
+            return Err(Error::InvalidConfig);
+        }
+        let mut best: Option<Self> = None;
+        let mut consider = |divisor: u64, extension: DivisorExtension| {
+            if (1..=0xffff).contains(&divisor) {
+                let plan = Self::with_divisor(clock, baud_rate, divisor as u16, extension);
+                if best.map_or(true, |best| plan.error_ppm.abs() < best.error_ppm.abs()) {
+                    best = Some(plan);
+                }
+            }
+        };
+
+        match variant {
+            DivisorVariant::Standard => {
+                for divisor in nearest_divisors(clock, baud_rate, DivisorExtension::None)? {
+                    consider(divisor, DivisorExtension::None);
+                }
+            }
+            DivisorVariant::DesignWare { dlf_bits } => {
+                let bits = dlf_bits.min(8);
+                // In units of 1 / 2^bits
+                let base = (baud_rate as u64)
+                    .checked_mul(16)
+                    .ok_or(Error::UnsupportedDivisor)?;
+                let divisor = (clock as u64)
+                    .checked_mul(1 << bits)
+                    .and_then(|clock| clock.checked_add(base / 2))
+                    .ok_or(Error::UnsupportedDivisor)?
+                    / base;
+                consider(
+                    divisor >> bits,
+                    DivisorExtension::Fraction {
+                        dlf: (divisor & ((1 << bits) - 1)) as u8,
+                        bits,
+                    },
+                );
+            }
+            DivisorVariant::NxpLpc => {
+                for mulval in 1..=15 {
+                    for divaddval in 0..mulval {
+                        let extension = DivisorExtension::FractionalDivider { mulval, divaddval };
+                        for divisor in nearest_divisors(clock, baud_rate, extension)? {
+                            // The fractional divider only works with DLL/DLH >= 3
+                            if divaddval == 0 || divisor >= 3 {
+                                consider(divisor, extension);
+                            }
+                        }
+                    }
+                }
+            }
+            DivisorVariant::Ox16C950 => {
+                // More samples per bit tolerate more noise, so prefer them when the error is the same
+                for samples in (4..=16).rev() {
+                    let extension = DivisorExtension::SamplesPerBit(samples);
+                    for divisor in nearest_divisors(clock, baud_rate, extension)? {
+                        consider(divisor, extension);
+                    }
+                }
+            }
+            DivisorVariant::MediaTek => {
+                // The smallest divisor leaves the finest sample count, as Linux does
+                let baud_rate = baud_rate as u64;
+                let base = baud_rate
+                    .checked_mul(256)
+                    .ok_or(Error::UnsupportedDivisor)?;
+                let divisor = (clock as u64).div_ceil(base).max(1);
+                let ideal = divisor
+                    .checked_mul(baud_rate)
+                    .ok_or(Error::UnsupportedDivisor)?;
+                let samples = ((clock as u64).saturating_add(ideal / 2) / ideal).clamp(2, 256);
+                consider(divisor, DivisorExtension::SampleCount(samples as u16));
+            }
+        }
+        best.ok_or(Error::UnsupportedDivisor)
+    }
+
+    /// Like [`BaudPlan::new`], but returns [`Error::UnsupportedDivisor`] if the baud rate is off by
+    /// more than `tolerance_ppm` parts per million.
+    ///
+    /// Both ends of a link may be off, so 2% (20000) is about as much as can work.
+    pub fn with_tolerance(
+        clock: usize,
+        baud_rate: usize,
+        tolerance_ppm: u32,
+    ) -> Result<Self, Error> {
+        Self::new(clock, baud_rate)?.within(tolerance_ppm)
+    }
+
+    /// Returns [`Error::UnsupportedDivisor`] if the baud rate is off by more than `tolerance_ppm`
+    pub fn within(self, tolerance_ppm: u32) -> Result<Self, Error> {
+        if self.error_ppm.unsigned_abs() > tolerance_ppm {
+            Err(Error::UnsupportedDivisor)
+        } else {
+            Ok(self)
+        }
+    }
+
+    /// Returns [`Error::InvalidConfig`] if `extension` is out of the range its registers take
+    ///
+    /// The ranges are those listed on [`DivisorVariant`]. Plans made by [`BaudPlan::for_variant`]
+    /// are always in range.
+    pub fn validate(self) -> Result<Self, Error> {
+        let valid = match self.extension {
+            DivisorExtension::None => true,
+            DivisorExtension::Fraction { dlf, bits } => bits <= 8 && (dlf as u16) < 1 << bits,
+            DivisorExtension::FractionalDivider { mulval, divaddval } => {
+                (1..=15).contains(&mulval) && divaddval <= 15
+            }
+            DivisorExtension::SamplesPerBit(samples) => (4..=16).contains(&samples),
+            DivisorExtension::SampleCount(samples) => (2..=256).contains(&samples),
+        };
+        if valid {
+            Ok(self)
+        } else {
+            Err(Error::InvalidConfig)
+        }
+    }
+
+    fn with_divisor(
+        clock: usize,
+        baud_rate: usize,
+        divisor: u16,
+        extension: DivisorExtension,
+    ) -> Self {
+        let (numerator, denominator) = divider(divisor, extension);
+        // Both scaled by denominator: the clock which would give exactly baud_rate, and the clock
+        let ideal_clock = numerator as i128 * baud_rate as i128;
+        let clock_scaled = clock as i128 * denominator as i128;
+        Self {
+            divisor,
+            extension,
+            actual_baud_rate: Self::baud_rate_with(clock, divisor, extension),
+            error_ppm: ((clock_scaled - ideal_clock) * 1_000_000 / ideal_clock) as i32,
+        }
+    }
+
+    /// The baud rate given by `divisor`, rounded to the nearest integer, or 0 if `divisor` is 0
+    pub fn baud_rate(clock: usize, divisor: u16) -> usize {
+        Self::baud_rate_with(clock, divisor, DivisorExtension::None)
+    }
+
+    /// The baud rate given by `divisor` and `extension`, rounded to the nearest integer, or 0 if
+    /// the clock isn't divided by anything
+    pub fn baud_rate_with(clock: usize, divisor: u16, extension: DivisorExtension) -> usize {
+        let (numerator, denominator) = divider(divisor, extension);
+        if numerator == 0 {
+            return 0;
+        }
+        ((clock as u128 * denominator as u128 + numerator as u128 / 2) / numerator as u128) as usize
+    }
+
+    /// Plans for those of [`STANDARD_RATES`](Self::STANDARD_RATES) which `clock` can reach within
+    /// `tolerance_ppm`
+    pub fn achievable(clock: usize, tolerance_ppm: u32) -> impl Iterator<Item = Self> {
+        Self::STANDARD_RATES.iter().filter_map(move |&baud_rate| {
+            Self::with_tolerance(clock, baud_rate, tolerance_ppm).ok()
+        })
+    }
+
+    /// Error of the actual baud rate relative to the requested one, in percent
+    pub fn error_percent(&self) -> f32 {
+        self.error_ppm as f32 / 10_000.0
+    }
+}
+
+/// The clock is divided by `numerator / denominator`
+pub(crate) fn divider(divisor: u16, extension: DivisorExtension) -> (u64, u64) {
+    let divisor = divisor as u64;
+    match extension {
+        DivisorExtension::None => (16 * divisor, 1),
+        DivisorExtension::Fraction { dlf, bits } => {
+            (16 * ((divisor << bits) + dlf as u64), 1 << bits)
+        }
+        DivisorExtension::FractionalDivider { mulval, divaddval } => (
+            16 * divisor * (mulval as u64 + divaddval as u64),
+            mulval as u64,
+        ),
+        DivisorExtension::SamplesPerBit(samples) => (samples as u64 * divisor, 1),
+        DivisorExtension::SampleCount(samples) => (samples as u64 * divisor, 1),
+    }
+}
+
+/// The divisors just below and above the one giving exactly `baud_rate` with `extension`
+///
+/// Returns [`Error::UnsupportedDivisor`] if the arithmetic overflows, which only happens for
+/// clocks or baud rates far beyond what a divisor can reach.
+fn nearest_divisors(
+    clock: usize,
+    baud_rate: usize,
+    extension: DivisorExtension,
+) -> Result<[u64; 2], Error> {
+    let (numerator, denominator) = divider(1, extension);
+    let ideal = (baud_rate as u64)
+        .checked_mul(numerator)
+        .ok_or(Error::UnsupportedDivisor)?;
+    let clock = (clock as u64)
+        .checked_mul(denominator)
+        .ok_or(Error::UnsupportedDivisor)?;
+    let lower = clock / ideal;
+    Ok([lower, lower.saturating_add(1)])
+}
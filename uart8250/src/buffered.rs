@@ -0,0 +1,249 @@
+/*!
+# Interrupt driven buffered driver
+
+[`BufferedUart8250`] owns a receive and a transmit ring buffer. It is meant to live in a `static`
+shared by the code using it and the interrupt handler, which calls
+[`BufferedUart8250::handle_interrupt`]:
+
+```no_run
+use uart8250::access::{DefaultLayout, Mmio};
+use uart8250::buffered::BufferedUart8250;
+use uart8250::MmioUart8250;
+
+// SAFETY: 0x1000_0000 is the UART, and it is not used anywhere else.
+static UART: BufferedUart8250<Mmio<'static>, 256, 256> =
+    BufferedUart8250::new(unsafe { MmioUart8250::with_layout(0x1000_0000, DefaultLayout) });
+
+fn uart_interrupt_handler() {
+    UART.handle_interrupt();
+}
+
+fn echo() {
+    UART.init(11_059_200, 115200);
+    let mut buf = [0; 16];
+    loop {
+        if let Ok(count) = UART.read(&mut buf) {
+            UART.write(&buf[..count]);
+        }
+    }
+}
+```
+
+The receive interrupts stay enabled, and the interrupt handler moves everything the UART has
+received into the receive buffer. The transmit interrupt is only enabled while there is something
+in the transmit buffer, so a level triggered THRE interrupt can't storm.
+Every register access and every change to the buffers is made in a critical section, including
+register accesses through [`BufferedUart8250::with_uart`].
+*/
+
+use core::cell::RefCell;
+
+use critical_section::{CriticalSection, Mutex};
+
+use crate::access::RegisterAccess;
+use crate::{InterruptType, LineError, Uart8250, IER, LSR};
+
+/// Bound on the number of interrupt causes handled in one call, in case the hardware misbehaves
+const MAX_INTERRUPT_CAUSES: usize = 8;
+
+/// Bound on the number of characters received in one go, twice the FIFO of the OX16C950
+const MAX_RECEIVE: usize = 256;
+
+/// FIFO size of the 16550A
+const DEFAULT_TX_BURST: usize = 16;
+
+/// A fixed size FIFO queue of bytes
+struct Ring<const N: usize> {
+    buf: [u8; N],
+    head: usize,
+    len: usize,
+}
+
+impl<const N: usize> Ring<N> {
+    const fn new() -> Self {
+        Self {
+            buf: [0; N],
+            head: 0,
+            len: 0,
+        }
+    }
+
+    fn push(&mut self, byte: u8) -> bool {
+        if self.len == N {
+            return false;
+        }
+        self.buf[(self.head + self.len) % N] = byte;
+        self.len += 1;
+        true
+    }
+
+    fn pop(&mut self) -> Option<u8> {
+        if self.len == 0 {
+            return None;
+        }
+        let byte = self.buf[self.head];
+        self.head = (self.head + 1) % N;
+        self.len -= 1;
+        Some(byte)
+    }
+}
+
+struct Buffers<const RX: usize, const TX: usize> {
+    rx: Ring<RX>,
+    tx: Ring<TX>,
+    /// The first error since the last read
+    error: Option<LineError>,
+}
+
+/// # Interrupt driven 8250 UART with `RX` and `TX` byte ring buffers
+///
+/// See the [module documentation](self) for how to share it with the interrupt handler.
+pub struct BufferedUart8250<A: RegisterAccess, const RX: usize, const TX: usize> {
+    uart: Uart8250<A>,
+    buffers: Mutex<RefCell<Buffers<RX, TX>>>,
+    tx_burst: usize,
+}
+
+// Like `critical_section::Mutex`: the UART is only reached in critical sections, so only one
+// thread uses it at a time, which `Send` allows.
+unsafe impl<A: RegisterAccess + Send, const RX: usize, const TX: usize> Sync
+    for BufferedUart8250<A, RX, TX>
+{
+}
+
+impl<A: RegisterAccess, const RX: usize, const TX: usize> BufferedUart8250<A, RX, TX> {
+    pub const fn new(uart: Uart8250<A>) -> Self {
+        Self {
+            uart,
+            buffers: Mutex::new(RefCell::new(Buffers {
+                rx: Ring::new(),
+                tx: Ring::new(),
+                error: None,
+            })),
+            tx_burst: DEFAULT_TX_BURST,
+        }
+    }
+
+    /// Writes up to `burst` characters each time THR is empty, instead of 16.
+    ///
+    /// Use 1 for UARTs without a FIFO, such as the 8250 and 16450, or the depth found by
+    /// [`probe`](Uart8250::probe).
+    pub const fn with_tx_burst(mut self, burst: usize) -> Self {
+        self.tx_burst = burst;
+        self
+    }
+
+    /// Initialises the UART like [`Uart8250::init`], with the receive interrupts enabled.
+    pub fn init(&self, clock: usize, baud_rate: usize) {
+        self.with_uart(|uart| {
+            uart.init(clock, baud_rate);
+            uart.set_ier(IER::RDAI | IER::RLSI);
+        })
+    }
+
+    /// Calls `f` with the underlying UART in a critical section, returning what it returns
+    pub fn with_uart<R>(&self, f: impl FnOnce(&Uart8250<A>) -> R) -> R {
+        critical_section::with(|_| f(&self.uart))
+    }
+
+    /// Services the UART interrupt: empties the receiver into the receive buffer, and refills
+    /// the transmitter from the transmit buffer.
+    pub fn handle_interrupt(&self) {
+        critical_section::with(|cs| {
+            for _ in 0..MAX_INTERRUPT_CAUSES {
+                match self.uart.read_interrupt_type() {
+                    Some(InterruptType::ReceiverLineStatus)
+                    | Some(InterruptType::ReceivedDataAvailable)
+                    | Some(InterruptType::Timeout) => self.receive(cs),
+                    // Reading IIR has cleared it
+                    Some(InterruptType::TransmitterHoldingRegisterEmpty) => self.transmit(cs),
+                    Some(InterruptType::ModemStatus) => {
+                        self.uart.read_msr();
+                    }
+                    Some(InterruptType::Reserved) | None => break,
+                }
+            }
+        })
+    }
+
+    /// Move everything received into the receive buffer, keeping the first error
+    fn receive(&self, cs: CriticalSection<'_>) {
+        let mut buffers = self.buffers.borrow_ref_mut(cs);
+        for _ in 0..MAX_RECEIVE {
+            let lsr = self.uart.raw_lsr();
+            if let Some(error) = LineError::from_lsr(lsr) {
+                buffers.error.get_or_insert(error);
+            }
+            if !lsr.contains(LSR::DR) {
+                break;
+            }
+            // The character is dropped if the buffer is full, so the interrupt is cleared
+            if !buffers.rx.push(self.uart.read_rbr()) {
+                buffers.error.get_or_insert(LineError::Overrun);
+            }
+        }
+    }
+
+    /// Refill the transmitter, and stop the THRE interrupt once the transmit buffer is empty
+    fn transmit(&self, cs: CriticalSection<'_>) {
+        let mut buffers = self.buffers.borrow_ref_mut(cs);
+        for _ in 0..self.tx_burst {
+            match buffers.tx.pop() {
+                Some(byte) => self.uart.write_thr(byte),
+                None => break,
+            }
+        }
+        if buffers.tx.len == 0 {
+            self.uart.modify_ier(|ier| ier & !IER::THREI);
+        }
+    }
+
+    /// Reads from the receive buffer without waiting, returning how many bytes were read.
+    ///
+    /// Returns the first error the receiver reported since the last call instead, once. An
+    /// overrun is also reported when the receive buffer was full.
+    pub fn read(&self, buf: &mut [u8]) -> Result<usize, LineError> {
+        critical_section::with(|cs| {
+            let mut buffers = self.buffers.borrow_ref_mut(cs);
+            if let Some(error) = buffers.error.take() {
+                return Err(error);
+            }
+            let mut count = 0;
+            while count < buf.len() {
+                match buffers.rx.pop() {
+                    Some(byte) => buf[count] = byte,
+                    None => break,
+                }
+                count += 1;
+            }
+            Ok(count)
+        })
+    }
+
+    /// Queues as much of `buf` as fits in the transmit buffer without waiting, returning how many
+    /// bytes were queued.
+    pub fn write(&self, buf: &[u8]) -> usize {
+        critical_section::with(|cs| {
+            let mut buffers = self.buffers.borrow_ref_mut(cs);
+            let count = buf
+                .iter()
+                .take_while(|&&byte| buffers.tx.push(byte))
+                .count();
+            if count > 0 {
+                // THRE interrupts at once if THR is already empty
+                self.uart.modify_ier(|ier| ier | IER::THREI);
+            }
+            count
+        })
+    }
+
+    /// Number of bytes waiting in the receive buffer
+    pub fn rx_len(&self) -> usize {
+        critical_section::with(|cs| self.buffers.borrow_ref(cs).rx.len)
+    }
+
+    /// Number of bytes waiting in the transmit buffer
+    pub fn tx_len(&self) -> usize {
+        critical_section::with(|cs| self.buffers.borrow_ref(cs).tx.len)
+    }
+}
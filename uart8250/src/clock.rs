@@ -0,0 +1,167 @@
+/*!
+# Input clock discovery
+
+[`Uart8250::measure_clock`] finds the input clock of a UART by timing how long it takes to send
+characters in loopback mode, using a [`Monotonic`] time source supplied by the caller. Nothing
+goes out on the line.
+
+```no_run
+use uart8250::clock::{nearest_common_clock, Monotonic};
+use uart8250::MmioUart8250;
+
+struct Timer;
+
+impl Monotonic for Timer {
+    fn now(&self) -> u64 {
+        // e.g. the RISC-V `time` CSR
+        0
+    }
+
+    fn ticks_per_second(&self) -> u64 {
+        10_000_000
+    }
+}
+
+let uart: MmioUart8250 = unsafe { MmioUart8250::new(0x1000_0000) };
+let clock = uart.measure_clock(&Timer, 1, 256).unwrap();
+let clock = nearest_common_clock(clock, 20_000).unwrap_or(clock);
+uart.init(clock, 115200);
+```
+*/
+
+use crate::access::RegisterAccess;
+use crate::baud::divider;
+use crate::uart::LCR_8N1;
+use crate::{BaudPlan, Error, Uart8250, IER, LSR, MCR};
+
+/// A time source which never goes backwards
+pub trait Monotonic {
+    /// Current time in ticks
+    fn now(&self) -> u64;
+
+    /// Ticks per second
+    fn ticks_per_second(&self) -> u64;
+}
+
+/// Input clocks often found on UARTs, in ascending order
+pub const COMMON_CLOCKS: &[usize] = &[
+    1_843_200,
+    3_686_400,
+    7_372_800,
+    11_059_200,
+    14_745_600,
+    18_432_000,
+    22_118_400,
+    24_000_000,
+    24_576_000,
+    25_000_000,
+    26_000_000,
+    29_491_200,
+    33_333_333,
+    40_000_000,
+    44_236_800,
+    48_000_000,
+    50_000_000,
+    62_500_000,
+    66_666_666,
+    100_000_000,
+    125_000_000,
+    200_000_000,
+];
+
+/// Get the one of [`COMMON_CLOCKS`] nearest to `clock`, if it is within `tolerance_ppm`
+pub fn nearest_common_clock(clock: usize, tolerance_ppm: u32) -> Option<usize> {
+    COMMON_CLOCKS
+        .iter()
+        .copied()
+        .min_by_key(|&common| common.abs_diff(clock))
+        .filter(|&common| {
+            common.abs_diff(clock) as u64 * 1_000_000 <= tolerance_ppm as u64 * common as u64
+        })
+}
+
+/// Bits of a character with 8 data bits, no parity and 1 stop bit
+const BITS_PER_CHARACTER: u64 = 10;
+
+impl<A: RegisterAccess> Uart8250<A> {
+    /// Estimates the input clock by sending `characters` characters in loopback mode with
+    /// `divisor` and timing them with `time`.
+    ///
+    /// The measurement takes `characters * 160 * divisor / clock` seconds, which should be long
+    /// compared to a tick of `time`. Variant specific divisor registers are kept as they are.
+    /// LCR, MCR, IER and the divisor are restored afterwards, and characters received meanwhile
+    /// are lost.
+    ///
+    /// Returns [`Error::NotDetected`] if the transmitter doesn't finish within a second.
+    pub fn measure_clock<T: Monotonic>(
+        &self,
+        time: &T,
+        divisor: u16,
+        characters: usize,
+    ) -> Result<usize, Error> {
+        if divisor == 0 || characters == 0 {
+            return Err(Error::InvalidConfig);
+        }
+        let lcr = self.read_lcr();
+        let mcr = self.read_mcr();
+        let ier = self.ier();
+        // The clock doesn't matter, only the divisor is written back
+        let previous = self.read_baud_plan(0);
+
+        self.set_ier(IER::empty());
+        self.write_lcr(LCR_8N1);
+        self.write_mcr(mcr | MCR::LOOPBACK.bits());
+        let plan = BaudPlan {
+            divisor,
+            ..previous
+        };
+        self.write_baud_plan(&plan);
+        let elapsed = self.time_transmission(time, characters);
+
+        // Drop the looped back characters and the overrun they caused
+        for _ in 0..characters {
+            if !self.raw_lsr().contains(LSR::DR) {
+                break;
+            }
+            self.read_rbr();
+        }
+        self.write_baud_plan(&previous);
+        self.write_lcr(lcr);
+        self.write_mcr(mcr);
+        self.set_ier(ier);
+
+        let elapsed = elapsed?;
+        if elapsed == 0 {
+            return Err(Error::NotDetected);
+        }
+        let (numerator, denominator) = divider(divisor, plan.extension);
+        let clock = characters as u128
+            * BITS_PER_CHARACTER as u128
+            * numerator as u128
+            * time.ticks_per_second() as u128
+            / (denominator as u128 * elapsed as u128);
+        Ok(clock as usize)
+    }
+
+    /// Ticks taken to send `characters` back to back, starting with the transmitter idle
+    fn time_transmission<T: Monotonic>(&self, time: &T, characters: usize) -> Result<u64, Error> {
+        let timeout = time.ticks_per_second();
+        let wait_for = |flag: LSR, since: u64| {
+            while !self.raw_lsr().contains(flag) {
+                if time.now().wrapping_sub(since) > timeout {
+                    return Err(Error::NotDetected);
+                }
+            }
+            Ok(())
+        };
+
+        wait_for(LSR::DHRE, time.now())?;
+        let start = time.now();
+        for _ in 0..characters {
+            wait_for(LSR::THRE, start)?;
+            self.write_thr(0);
+        }
+        wait_for(LSR::DHRE, start)?;
+        Ok(time.now().wrapping_sub(start))
+    }
+}
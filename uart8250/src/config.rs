@@ -0,0 +1,176 @@
+use crate::uart::{LCR_STOP_BITS, LCR_WORD_LENGTH};
+use crate::{Error, Parity, FCR, IER};
+
+/// Whether the FIFOs are used
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum FifoMode {
+    /// One character at a time, like the 8250 and 16450
+    Disabled,
+    /// FCR\[0\]
+    Enabled,
+}
+
+/// Receiver FIFO trigger level, FCR\[7:6\]
+///
+/// The number of characters depends on whether the 16750's 64-byte FIFO is enabled.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum TriggerLevel {
+    /// 1 character
+    Bytes1,
+    /// 4 characters, or 16 with the 64-byte FIFO
+    Bytes4,
+    /// 8 characters, or 32 with the 64-byte FIFO
+    Bytes8,
+    /// 14 characters, or 56 with the 64-byte FIFO
+    Bytes14,
+}
+
+impl TriggerLevel {
+    /// Number of characters in the receiver FIFO which raise the received data available interrupt
+    pub const fn bytes(self, fifo_64byte: bool) -> usize {
+        match (self, fifo_64byte) {
+            (TriggerLevel::Bytes1, _) => 1,
+            (TriggerLevel::Bytes4, false) => 4,
+            (TriggerLevel::Bytes8, false) => 8,
+            (TriggerLevel::Bytes14, false) => 14,
+            (TriggerLevel::Bytes4, true) => 16,
+            (TriggerLevel::Bytes8, true) => 32,
+            (TriggerLevel::Bytes14, true) => 56,
+        }
+    }
+}
+
+/// # FIFO settings of a UART
+///
+/// Applied with [`Uart8250::set_fifo_config`](crate::Uart8250::set_fifo_config) and read back
+/// from the driver's copy of FCR with [`Uart8250::fifo_config`](crate::Uart8250::fifo_config).
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct FifoConfig {
+    /// FCR\[0\]
+    pub enable: bool,
+    /// FCR\[1\], not kept once written
+    pub clear_rx: bool,
+    /// FCR\[2\], not kept once written
+    pub clear_tx: bool,
+    /// FCR\[3\]
+    pub dma_mode: bool,
+    /// FCR\[5\] (16750)
+    pub fifo_64byte: bool,
+    pub rx_trigger: TriggerLevel,
+}
+
+impl FifoConfig {
+    /// FIFOs enabled and cleared, interrupting on every character, the same as
+    /// [`Uart8250::init`](crate::Uart8250::init) apart from the clearing
+    pub const fn new() -> Self {
+        Self {
+            enable: true,
+            clear_rx: true,
+            clear_tx: true,
+            dma_mode: false,
+            fifo_64byte: false,
+            rx_trigger: TriggerLevel::Bytes1,
+        }
+    }
+
+    /// Get the FCR value for these settings
+    pub fn fcr(&self) -> FCR {
+        let mut fcr = FCR::from_bits_truncate(match self.rx_trigger {
+            TriggerLevel::Bytes1 => 0b0000_0000,
+            TriggerLevel::Bytes4 => 0b0100_0000,
+            TriggerLevel::Bytes8 => 0b1000_0000,
+            TriggerLevel::Bytes14 => 0b1100_0000,
+        });
+        fcr.set(FCR::ENABLE, self.enable);
+        fcr.set(FCR::CLEAR_RX, self.clear_rx);
+        fcr.set(FCR::CLEAR_TX, self.clear_tx);
+        fcr.set(FCR::DMA_MODE, self.dma_mode);
+        fcr.set(FCR::FIFO_64BYTE, self.fifo_64byte);
+        fcr
+    }
+
+    /// Decode an FCR value
+    pub fn from_fcr(fcr: FCR) -> Self {
+        Self {
+            enable: fcr.contains(FCR::ENABLE),
+            clear_rx: fcr.contains(FCR::CLEAR_RX),
+            clear_tx: fcr.contains(FCR::CLEAR_TX),
+            dma_mode: fcr.contains(FCR::DMA_MODE),
+            fifo_64byte: fcr.contains(FCR::FIFO_64BYTE),
+            rx_trigger: match fcr.bits() & 0b1100_0000 {
+                0b0000_0000 => TriggerLevel::Bytes1,
+                0b0100_0000 => TriggerLevel::Bytes4,
+                0b1000_0000 => TriggerLevel::Bytes8,
+                _ => TriggerLevel::Bytes14,
+            },
+        }
+    }
+
+    /// Number of characters which raise the received data available interrupt
+    pub const fn rx_trigger_bytes(&self) -> usize {
+        self.rx_trigger.bytes(self.fifo_64byte)
+    }
+}
+
+impl Default for FifoConfig {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// # Line settings of a UART
+///
+/// Applied with [`Uart8250::apply_line_config`](crate::Uart8250::apply_line_config) and read
+/// back with [`Uart8250::line_config`](crate::Uart8250::line_config).
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct LineConfig {
+    pub baud_rate: usize,
+    /// 5..=8
+    pub data_bits: u8,
+    pub parity: Parity,
+    /// 1 or 2, where 2 means 1.5 with 5 data bits
+    pub stop_bits: u8,
+    pub fifo: FifoMode,
+    pub interrupts: IER,
+}
+
+impl LineConfig {
+    /// 8 data bits, no parity, 1 stop bit, FIFO enabled and received data available interrupt
+    /// enabled, the same as [`Uart8250::init`](crate::Uart8250::init)
+    pub const fn new(baud_rate: usize) -> Self {
+        Self {
+            baud_rate,
+            data_bits: 8,
+            parity: Parity::No,
+            stop_bits: 1,
+            fifo: FifoMode::Enabled,
+            interrupts: IER::RDAI,
+        }
+    }
+
+    /// Check the settings, and get the LCR value with DLAB clear
+    pub(crate) fn lcr(&self) -> Result<u8, Error> {
+        if !(5..=8).contains(&self.data_bits) {
+            return Err(Error::InvalidConfig);
+        }
+        let stop_bits = match self.stop_bits {
+            1 => 0,
+            2 => LCR_STOP_BITS,
+            _ => return Err(Error::InvalidConfig),
+        };
+        Ok(self.parity.lcr() | stop_bits | (self.data_bits - 5))
+    }
+
+    /// Decode LCR, ignoring DLAB and break
+    pub(crate) fn from_lcr(lcr: u8, baud_rate: usize, fifo: FifoMode, interrupts: IER) -> Self {
+        Self {
+            baud_rate,
+            data_bits: (lcr & LCR_WORD_LENGTH) + 5,
+            // Without LCR[3] there is no parity, whatever LCR[5:4] are
+            parity: Parity::from_lcr(lcr).unwrap_or(Parity::No),
+            stop_bits: ((lcr & LCR_STOP_BITS) >> 2) + 1,
+            fifo,
+            interrupts,
+        }
+    }
+}
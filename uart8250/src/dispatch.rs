@@ -0,0 +1,162 @@
+/*!
+# Interrupt dispatch
+
+[`Uart8250::dispatch_interrupts`] is a complete interrupt service routine: it reads IIR until no
+interrupt is pending, clears each cause the way the UART expects, and calls an
+[`InterruptHandler`] with what it found.
+
+```no_run
+use uart8250::dispatch::InterruptHandler;
+use uart8250::line_break::RxEvent;
+use uart8250::MmioUart8250;
+
+struct Echo {
+    pending: Option<u8>,
+}
+
+impl InterruptHandler for Echo {
+    fn receive(&mut self, event: RxEvent) {
+        if let RxEvent::Data(byte) = event {
+            self.pending = Some(byte);
+        }
+    }
+
+    fn transmit(&mut self) -> Option<u8> {
+        self.pending.take()
+    }
+}
+
+let uart: MmioUart8250 = unsafe { MmioUart8250::new(0x1000_0000) };
+let mut echo = Echo { pending: None };
+// In the interrupt handler
+uart.dispatch_interrupts(&mut echo).ok();
+```
+
+Each cause is cleared as follows:
+
+| Cause                     | Cleared by                                            |
+| ------------------------- | ----------------------------------------------------- |
+| Receiver line status      | reading LSR                                           |
+| Received data / timeout   | reading RBR until LSR\[0\] is clear                   |
+| THR empty                 | reading IIR, then writing THR                         |
+| Modem status              | reading MSR                                           |
+
+Reading IIR clears a THRE interrupt even when nothing is written, so LSR is checked for an empty
+THR on every cause while THREI is enabled, and the handler is asked for characters each time.
+*/
+
+use crate::access::RegisterAccess;
+use crate::line_break::RxEvent;
+use crate::registers::offset;
+use crate::{Error, InterruptId, InterruptType, LineError, Uart8250, FCR, IER, LSR, MSR};
+
+/// Bound on the number of interrupt causes handled in one call, in case the hardware misbehaves
+pub const MAX_INTERRUPT_CAUSES: usize = 8;
+
+/// Bound on the number of characters received for one cause, twice the FIFO of the OX16C950
+const MAX_RECEIVE: usize = 256;
+
+/// What to do for each interrupt cause, see [`Uart8250::dispatch_interrupts`]
+///
+/// Every method except [`receive`](InterruptHandler::receive) does nothing by default.
+pub trait InterruptHandler {
+    /// Called with each character read from RBR
+    fn receive(&mut self, event: RxEvent);
+
+    /// Called when LSR reports an error without a character to go with it
+    fn line_error(&mut self, error: LineError) {
+        let _ = error;
+    }
+
+    /// Called while THR is empty, until it returns `None` or the transmit FIFO is full
+    ///
+    /// Disable THREI when there is nothing left to send, or a level triggered THRE interrupt
+    /// keeps coming back.
+    fn transmit(&mut self) -> Option<u8> {
+        None
+    }
+
+    /// Called with MSR when the modem lines changed
+    fn modem_status(&mut self, msr: MSR) {
+        let _ = msr;
+    }
+}
+
+impl<A: RegisterAccess> Uart8250<A> {
+    /// Services every pending interrupt cause, returning how many were handled.
+    ///
+    /// Stops when a cause is still pending after [`MAX_INTERRUPT_CAUSES`], returning its IIR as
+    /// [`Error::UnexpectedRegisterValue`]. A reserved IIR value is returned the same way.
+    pub fn dispatch_interrupts<H: InterruptHandler>(
+        &self,
+        handler: &mut H,
+    ) -> Result<usize, Error> {
+        let unexpected = |iir: InterruptId| Error::UnexpectedRegisterValue {
+            offset: offset::IIR,
+            value: iir.bits(),
+        };
+        for causes in 0..MAX_INTERRUPT_CAUSES {
+            let iir = self.interrupt_id();
+            match iir.interrupt_type() {
+                None => return Ok(causes),
+                Some(InterruptType::Reserved) => return Err(unexpected(iir)),
+                Some(interrupt_type) => self.service(interrupt_type, handler),
+            }
+        }
+        let iir = self.interrupt_id();
+        match iir.interrupt_type() {
+            None => Ok(MAX_INTERRUPT_CAUSES),
+            // Reading IIR may have cleared THRE, so the characters are still written
+            Some(InterruptType::TransmitterHoldingRegisterEmpty) => {
+                self.refill(handler);
+                Err(unexpected(iir))
+            }
+            Some(_) => Err(unexpected(iir)),
+        }
+    }
+
+    /// Clear one interrupt cause
+    fn service<H: InterruptHandler>(&self, interrupt_type: InterruptType, handler: &mut H) {
+        // Clears the receiver line status interrupt
+        let mut lsr = self.raw_lsr();
+        if lsr.contains(LSR::DR) {
+            for _ in 0..MAX_RECEIVE {
+                handler.receive(RxEvent::new(self.read_rbr(), lsr));
+                lsr = self.raw_lsr();
+                if !lsr.contains(LSR::DR) {
+                    break;
+                }
+            }
+        } else if let Some(error) = LineError::from_lsr(lsr) {
+            handler.line_error(error);
+        }
+
+        if interrupt_type == InterruptType::TransmitterHoldingRegisterEmpty
+            || (lsr.contains(LSR::THRE) && self.ier().contains(IER::THREI))
+        {
+            self.refill(handler);
+        }
+
+        if interrupt_type == InterruptType::ModemStatus {
+            handler.modem_status(self.msr());
+        }
+    }
+
+    /// Fill the transmit FIFO from the handler
+    fn refill<H: InterruptHandler>(&self, handler: &mut H) {
+        let fcr = self.fcr();
+        let depth = if !fcr.contains(FCR::ENABLE) {
+            1
+        } else if fcr.contains(FCR::FIFO_64BYTE) {
+            64
+        } else {
+            16
+        };
+        for _ in 0..depth {
+            match handler.transmit() {
+                Some(byte) => self.write_thr(byte),
+                None => break,
+            }
+        }
+    }
+}
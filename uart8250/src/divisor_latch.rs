@@ -0,0 +1,102 @@
+/*!
+# Scoped divisor latch access
+
+While DLAB (LCR\[7\]) is set, offsets 0 and 1 are the divisor latch instead of RBR/THR and IER,
+so anything reading a character or changing IER at the same time gets the wrong register.
+[`Uart8250::with_divisor_latch`] sets DLAB only for the duration of a closure, which only gets
+the divisor registers:
+
+```
+use uart8250::model::{Chip, Model};
+use uart8250::Uart8250;
+
+let model = Model::new(Chip::U16550A);
+let uart = Uart8250::from_access(&model);
+uart.init(1_843_200, 115200);
+
+let old = uart.with_divisor_latch(|latch| {
+    let old = latch.divisor();
+    latch.write_divisor(12);
+    old
+});
+assert_eq!((old, model.divisor()), (1, 12));
+```
+
+The UART interrupts are masked in IER first, and LCR and IER are restored afterwards, even if
+the closure panics. With feature `critical-section` the whole access is made in a critical
+section, so an interrupt handler can't touch the UART in the meantime.
+*/
+
+use crate::access::RegisterAccess;
+use crate::uart::LCR_DLAB;
+use crate::{Uart8250, FCR, IER};
+
+/// # The divisor latch of an 8250 UART
+///
+/// Only exists while DLAB is set, see [`Uart8250::with_divisor_latch`].
+pub struct Latch<'a, A: RegisterAccess> {
+    uart: &'a Uart8250<A>,
+}
+
+impl<A: RegisterAccess> Latch<'_, A> {
+    /// Read the divisor latch (DLH:DLL)
+    pub fn divisor(&self) -> u16 {
+        u16::from_le_bytes([self.uart.read_dll(), self.uart.read_dlh()])
+    }
+
+    /// Write the divisor latch (DLH:DLL)
+    pub fn write_divisor(&self, divisor: u16) {
+        let [low, high] = divisor.to_le_bytes();
+        self.uart.write_dll(low);
+        self.uart.write_dlh(high);
+    }
+
+    /// Read FCR, which some UARTs return at IIR while DLAB is set
+    ///
+    /// # Safety
+    ///
+    /// The UART must return FCR while DLAB is set, see [`Uart8250::read_fcr`].
+    pub(crate) unsafe fn read_fcr(&self) -> u8 {
+        self.uart.read_fcr()
+    }
+
+    /// Write FCR, which the 16750 only takes [`FCR::FIFO_64BYTE`] from while DLAB is set
+    pub(crate) fn set_fcr(&self, fcr: FCR) {
+        self.uart.set_fcr(fcr)
+    }
+}
+
+/// Restores LCR and then IER when dropped
+struct Restore<'a, A: RegisterAccess> {
+    uart: &'a Uart8250<A>,
+    lcr: u8,
+    ier: IER,
+}
+
+impl<A: RegisterAccess> Drop for Restore<'_, A> {
+    fn drop(&mut self) {
+        // IER is only reachable with DLAB clear
+        self.uart.write_lcr(self.lcr & !LCR_DLAB);
+        self.uart.set_ier(self.ier);
+        self.uart.write_lcr(self.lcr);
+    }
+}
+
+impl<A: RegisterAccess> Uart8250<A> {
+    /// Calls `f` with DLAB set and the UART interrupts masked, returning what it returns
+    ///
+    /// LCR, including DLAB, and IER are restored to what they were before.
+    pub fn with_divisor_latch<R, F: FnOnce(&Latch<'_, A>) -> R>(&self, f: F) -> R {
+        self.locked(|| {
+            let restore = Restore {
+                uart: self,
+                lcr: self.read_lcr(),
+                ier: self.ier(),
+            };
+            self.write_lcr(restore.lcr & !LCR_DLAB);
+            self.set_ier(IER::empty());
+            self.write_lcr(restore.lcr | LCR_DLAB);
+            f(&Latch { uart: self })
+        })
+    }
+}
@@ -0,0 +1,122 @@
+use core::fmt;
+
+use crate::LSR;
+
+/// An error of this driver
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Error {
+    /// A setting is out of range, e.g. 9 data bits or 3 stop bits
+    InvalidConfig,
+    /// The baud rate can't be reached closely enough with a 16-bit divisor from this clock
+    UnsupportedDivisor,
+    /// A register held a value which the chip shouldn't produce
+    UnexpectedRegisterValue {
+        /// Register index, see [`offset`](crate::registers::offset)
+        offset: usize,
+        value: u8,
+    },
+    /// Detection didn't find a consistent answer, e.g. nothing was received during autobaud
+    NotDetected,
+    /// An error reported by the receiver
+    Line(LineError),
+}
+
+impl From<LineError> for Error {
+    fn from(error: LineError) -> Self {
+        Error::Line(error)
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::InvalidConfig => f.write_str("invalid configuration"),
+            Error::UnsupportedDivisor => f.write_str("unsupported divisor"),
+            Error::UnexpectedRegisterValue { offset, value } => {
+                write!(f, "unexpected value {:#04x} in register {}", value, offset)
+            }
+            Error::NotDetected => f.write_str("nothing detected"),
+            Error::Line(error) => fmt::Display::fmt(error, f),
+        }
+    }
+}
+
+impl core::error::Error for Error {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        match self {
+            Error::Line(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+/// An error reported by the receiver in LSR
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum LineError {
+    /// A character was lost because the receiver was full (LSR\[1\])
+    Overrun,
+    /// The received character had the wrong parity (LSR\[2\])
+    Parity,
+    /// The received character had no valid stop bit (LSR\[3\])
+    Framing,
+    /// The line was held low for longer than a character (LSR\[4\])
+    Break,
+}
+
+impl LineError {
+    /// Get the most significant error indicated by `lsr`, if any
+    ///
+    /// A break usually comes with a framing error as well, so it is reported first.
+    pub fn from_lsr(lsr: LSR) -> Option<Self> {
+        if lsr.contains(LSR::OE) {
+            Some(LineError::Overrun)
+        } else if lsr.contains(LSR::BI) {
+            Some(LineError::Break)
+        } else if lsr.contains(LSR::FE) {
+            Some(LineError::Framing)
+        } else if lsr.contains(LSR::PE) {
+            Some(LineError::Parity)
+        } else {
+            None
+        }
+    }
+}
+
+impl fmt::Display for LineError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LineError::Overrun => f.write_str("receiver overrun"),
+            LineError::Parity => f.write_str("parity error"),
+            LineError::Framing => f.write_str("framing error"),
+            LineError::Break => f.write_str("break condition"),
+        }
+    }
+}
+
+impl core::error::Error for LineError {}
+
+#[cfg(feature = "embedded-hal-nb")]
+impl embedded_hal_nb::serial::Error for LineError {
+    fn kind(&self) -> embedded_hal_nb::serial::ErrorKind {
+        use embedded_hal_nb::serial::ErrorKind;
+
+        match self {
+            LineError::Overrun => ErrorKind::Overrun,
+            LineError::Parity => ErrorKind::Parity,
+            LineError::Framing => ErrorKind::FrameFormat,
+            LineError::Break => ErrorKind::Other,
+        }
+    }
+}
+
+#[cfg(feature = "embedded-io")]
+impl embedded_io::Error for LineError {
+    fn kind(&self) -> embedded_io::ErrorKind {
+        match self {
+            LineError::Overrun => embedded_io::ErrorKind::Other,
+            LineError::Parity | LineError::Framing | LineError::Break => {
+                embedded_io::ErrorKind::InvalidData
+            }
+        }
+    }
+}
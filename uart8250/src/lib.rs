@@ -3,12 +3,44 @@
 
 This crate provides a struct with many methods to operate an 8250 UART.
 
+The UART can be reached through memory-mapped registers ([`MmioUart8250`]) or, on x86,
+through I/O ports ([`PioUart8250`]). Both share the same driver, [`Uart8250`].
+
 [REF: Serial Programming/8250 UART Programming](https://en.wikibooks.org/wiki/Serial_Programming/8250_UART_Programming#UART_Registers)
 */
 
 #![no_std]
 
+pub mod access;
+#[cfg(feature = "embedded-io-async")]
+pub mod asynch;
+pub mod autobaud;
+mod baud;
+#[cfg(feature = "buffered")]
+pub mod buffered;
+pub mod clock;
+mod config;
+pub mod dispatch;
+pub mod divisor_latch;
+mod error;
+pub mod line_break;
+#[cfg(feature = "model")]
+pub mod model;
+pub mod probe;
 pub mod registers;
+pub mod selftest;
+#[cfg(feature = "split")]
+pub mod split;
+mod status;
+pub mod typestate;
 mod uart;
 
-pub use uart::{ChipFifoInfo, InterruptType, MmioUart8250, Parity, IER, LSR, MSR};
+pub use baud::{BaudPlan, DivisorExtension, DivisorVariant};
+pub use config::{FifoConfig, FifoMode, LineConfig, TriggerLevel};
+pub use error::{Error, LineError};
+pub use status::{InterruptId, LineStatus};
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+pub use uart::PioUart8250;
+pub use uart::{
+    ChipFifoInfo, InterruptType, MmioUart8250, Parity, Uart8250, FCR, IER, LSR, MCR, MSR,
+};
@@ -0,0 +1,116 @@
+/*!
+# Break generation and detection
+
+A break holds the line low for longer than a character. It is sent by setting LCR\[6\], and the
+receiver loads it as a NUL character with LSR\[4\] set. [`Uart8250::read_event`] reads LSR once
+for each character, so the break isn't lost to another LSR read.
+
+```no_run
+use core::time::Duration;
+use uart8250::line_break::{Delay, RxEvent};
+use uart8250::MmioUart8250;
+
+struct Spin;
+
+impl Delay for Spin {
+    fn delay_us(&mut self, us: u32) {
+        for _ in 0..us {
+            core::hint::spin_loop();
+        }
+    }
+}
+
+let uart: MmioUart8250 = unsafe { MmioUart8250::new(0x1000_0000) };
+uart.init(1_843_200, 115200);
+uart.send_break(Duration::from_millis(250), &mut Spin);
+
+match uart.read_event() {
+    Some(RxEvent::Break { .. }) => { /* e.g. enter the boot loader */ }
+    Some(RxEvent::Data(byte)) => { let _ = byte; }
+    _ => {}
+}
+```
+*/
+
+use core::hint::spin_loop;
+use core::time::Duration;
+
+use crate::access::RegisterAccess;
+use crate::uart::LCR_BREAK;
+use crate::{LineError, Uart8250, LSR};
+
+/// Something which can wait, supplied by the caller
+pub trait Delay {
+    /// Wait for at least `us` microseconds
+    fn delay_us(&mut self, us: u32);
+}
+
+/// Something the receiver got, see [`Uart8250::read_event`]
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum RxEvent {
+    /// A character received intact
+    Data(u8),
+    /// A break, and the character it was loaded as, normally NUL
+    Break { byte: u8 },
+    /// A character received with an error
+    ///
+    /// With [`LineError::Overrun`], `byte` is fine but a later one was lost.
+    Error { byte: u8, error: LineError },
+}
+
+impl<A: RegisterAccess> Uart8250<A> {
+    /// Starts sending a break by setting LCR\[6\]
+    pub fn start_break(&self) {
+        self.write_lcr(self.read_lcr() | LCR_BREAK)
+    }
+
+    /// Stops sending a break by clearing LCR\[6\]
+    pub fn stop_break(&self) {
+        self.write_lcr(self.read_lcr() & !LCR_BREAK)
+    }
+
+    /// get whether a break is being sent (LCR\[6\])
+    pub fn is_sending_break(&self) -> bool {
+        self.read_lcr() & LCR_BREAK != 0
+    }
+
+    /// Sends a break for `duration`, timed by `delay`.
+    ///
+    /// Waits for the transmitter to finish first, so the characters before aren't cut off.
+    /// The break should last longer than a character to be detected.
+    pub fn send_break<D: Delay>(&self, duration: Duration, delay: &mut D) {
+        while !self.lsr().contains(LSR::DHRE) {
+            spin_loop();
+        }
+        self.start_break();
+        let mut remaining = duration.as_micros();
+        while remaining > 0 {
+            let us = remaining.min(u32::MAX as u128) as u32;
+            delay.delay_us(us);
+            remaining -= us as u128;
+        }
+        self.stop_break();
+    }
+
+    /// Reads the next received character together with its LSR bits, if there is one
+    pub fn read_event(&self) -> Option<RxEvent> {
+        let lsr = self.raw_lsr();
+        if !lsr.contains(LSR::DR) {
+            return None;
+        }
+        Some(RxEvent::new(self.read_rbr(), lsr))
+    }
+}
+
+impl RxEvent {
+    /// Classify `byte` by the LSR read just before it
+    pub(crate) fn new(byte: u8, lsr: LSR) -> Self {
+        if lsr.contains(LSR::BI) {
+            RxEvent::Break { byte }
+        } else if let Some(error) = LineError::from_lsr(lsr) {
+            RxEvent::Error { byte, error }
+        } else {
+            RxEvent::Data(byte)
+        }
+    }
+}
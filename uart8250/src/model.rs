@@ -0,0 +1,634 @@
+/*!
+# Behavioural model
+
+A software 8250/16450/16550A/16750 for running [`Uart8250`](crate::Uart8250) on a host, e.g. in `cargo test`.
+
+[`Model`] implements [`RegisterAccess`], so `Uart8250::from_access(&model)` drives it exactly as
+[`MmioUart8250`](crate::MmioUart8250) drives real registers. The test plays the other end of the line:
+it feeds received characters (optionally with parity/framing errors, breaks or noise), takes transmitted
+characters, drives the modem inputs and watches the interrupt line.
+
+Transmission is instant by default: a character written to THR leaves the UART at once.
+Use [`Model::hold_transmitter`] to keep characters in the transmitter and release them one at a time.
+
+```
+use uart8250::model::{Chip, Model};
+use uart8250::Uart8250;
+
+let model = Model::new(Chip::U16550A);
+let uart = Uart8250::from_access(&model);
+uart.init(1_843_200, 115200);
+
+model.receive(b'a');
+assert_eq!(uart.read_byte(), Some(b'a'));
+
+uart.write_byte(b'b');
+assert_eq!(model.transmitted(), Some(b'b'));
+```
+*/
+
+extern crate alloc;
+
+use alloc::collections::VecDeque;
+use core::cell::RefCell;
+
+use crate::access::RegisterAccess;
+use crate::registers::offset;
+use crate::{IER, LSR, MSR};
+
+/// The UART generation being modelled
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Chip {
+    /// No FIFO and no scratch register
+    U8250,
+    /// No FIFO
+    U16450,
+    /// 16-byte FIFOs
+    U16550A,
+    /// 16- or 64-byte FIFOs, sleep and low power mode, auto flow control
+    U16750,
+}
+
+impl Chip {
+    fn has_fifo(self) -> bool {
+        matches!(self, Chip::U16550A | Chip::U16750)
+    }
+
+    fn ier_mask(self) -> u8 {
+        match self {
+            Chip::U16750 => 0b0011_1111,
+            _ => 0b0000_1111,
+        }
+    }
+
+    fn mcr_mask(self) -> u8 {
+        match self {
+            Chip::U16750 => 0b0011_1111,
+            _ => 0b0001_1111,
+        }
+    }
+}
+
+const LSR_ERRORS: u8 = LSR::OE.bits() | LSR::PE.bits() | LSR::FE.bits() | LSR::BI.bits();
+const MSR_DELTAS: u8 = MSR::DCTS.bits() | MSR::DDSR.bits() | MSR::TERI.bits() | MSR::DDCD.bits();
+
+const LCR_DLAB: u8 = 0b1000_0000;
+const LCR_BREAK: u8 = 0b0100_0000;
+
+const MCR_DTR: u8 = 0b0000_0001;
+const MCR_RTS: u8 = 0b0000_0010;
+const MCR_OUT1: u8 = 0b0000_0100;
+const MCR_OUT2: u8 = 0b0000_1000;
+const MCR_LOOP: u8 = 0b0001_0000;
+
+const FCR_ENABLE: u8 = 0b0000_0001;
+const FCR_CLEAR_RX: u8 = 0b0000_0010;
+const FCR_CLEAR_TX: u8 = 0b0000_0100;
+const FCR_64BYTE: u8 = 0b0010_0000;
+
+/// Errors attached to a received character
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub struct RxFault {
+    pub parity: bool,
+    pub framing: bool,
+    pub break_condition: bool,
+}
+
+impl RxFault {
+    fn lsr(self) -> u8 {
+        let mut lsr = 0;
+        if self.parity {
+            lsr |= LSR::PE.bits();
+        }
+        if self.framing {
+            lsr |= LSR::FE.bits();
+        }
+        if self.break_condition {
+            lsr |= LSR::BI.bits();
+        }
+        lsr
+    }
+}
+
+/// State of the modem status inputs
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub struct ModemInputs {
+    pub cts: bool,
+    pub dsr: bool,
+    pub ri: bool,
+    pub dcd: bool,
+}
+
+impl ModemInputs {
+    fn msr(self) -> u8 {
+        let mut msr = 0;
+        if self.cts {
+            msr |= MSR::CTS.bits();
+        }
+        if self.dsr {
+            msr |= MSR::DSR.bits();
+        }
+        if self.ri {
+            msr |= MSR::RI.bits();
+        }
+        if self.dcd {
+            msr |= MSR::CD.bits();
+        }
+        msr
+    }
+}
+
+struct State {
+    chip: Chip,
+    dll: u8,
+    dlh: u8,
+    ier: u8,
+    lcr: u8,
+    mcr: u8,
+    fcr: u8,
+    scr: u8,
+    /// LSR error bits latched until LSR is read
+    lsr_errors: u8,
+    /// MSR delta bits latched until MSR is read
+    msr_deltas: u8,
+    /// Modem inputs driven by the other end of the line
+    inputs: ModemInputs,
+    /// Character and its LSR error bits
+    rx: VecDeque<(u8, u8)>,
+    tx: VecDeque<u8>,
+    hold_tx: bool,
+    thre_pending: bool,
+    timeout_pending: bool,
+    /// Characters which have left the UART
+    wire: VecDeque<u8>,
+}
+
+impl State {
+    fn new(chip: Chip) -> Self {
+        Self {
+            chip,
+            dll: 0,
+            dlh: 0,
+            ier: 0,
+            lcr: 0,
+            mcr: 0,
+            fcr: 0,
+            scr: 0,
+            lsr_errors: 0,
+            msr_deltas: 0,
+            inputs: ModemInputs::default(),
+            rx: VecDeque::new(),
+            tx: VecDeque::new(),
+            hold_tx: false,
+            thre_pending: false,
+            timeout_pending: false,
+            wire: VecDeque::new(),
+        }
+    }
+
+    fn fifo_enabled(&self) -> bool {
+        self.chip.has_fifo() && self.fcr & FCR_ENABLE != 0
+    }
+
+    fn fifo_64byte(&self) -> bool {
+        self.chip == Chip::U16750 && self.fifo_enabled() && self.fcr & FCR_64BYTE != 0
+    }
+
+    fn fifo_size(&self) -> usize {
+        if !self.fifo_enabled() {
+            1
+        } else if self.fifo_64byte() {
+            64
+        } else {
+            16
+        }
+    }
+
+    fn rx_trigger(&self) -> usize {
+        if !self.fifo_enabled() {
+            return 1;
+        }
+        let level = (self.fcr >> 6) as usize;
+        if self.fifo_64byte() {
+            [1, 16, 32, 56][level]
+        } else {
+            [1, 4, 8, 14][level]
+        }
+    }
+
+    fn loopback(&self) -> bool {
+        self.mcr & MCR_LOOP != 0
+    }
+
+    fn word_mask(&self) -> u8 {
+        0xff >> (3 - (self.lcr & 0b11))
+    }
+
+    /// Modem status bits as seen by the UART
+    fn msr_lines(&self) -> u8 {
+        if self.loopback() {
+            ModemInputs {
+                cts: self.mcr & MCR_RTS != 0,
+                dsr: self.mcr & MCR_DTR != 0,
+                ri: self.mcr & MCR_OUT1 != 0,
+                dcd: self.mcr & MCR_OUT2 != 0,
+            }
+            .msr()
+        } else {
+            self.inputs.msr()
+        }
+    }
+
+    /// Latch MSR deltas for the change from `old` to the current lines
+    fn update_msr(&mut self, old: u8) {
+        let new = self.msr_lines();
+        let changed = old ^ new;
+        if changed & MSR::CTS.bits() != 0 {
+            self.msr_deltas |= MSR::DCTS.bits();
+        }
+        if changed & MSR::DSR.bits() != 0 {
+            self.msr_deltas |= MSR::DDSR.bits();
+        }
+        if old & MSR::RI.bits() != 0 && new & MSR::RI.bits() == 0 {
+            self.msr_deltas |= MSR::TERI.bits();
+        }
+        if changed & MSR::CD.bits() != 0 {
+            self.msr_deltas |= MSR::DDCD.bits();
+        }
+    }
+
+    /// A character arrives at the receiver
+    fn receive(&mut self, byte: u8, errors: u8) {
+        let byte = byte & self.word_mask();
+        self.timeout_pending = false;
+        if self.rx.len() >= self.fifo_size() {
+            self.lsr_errors |= LSR::OE.bits();
+            if self.fifo_enabled() {
+                // The FIFO is kept, the character in the shift register is lost
+                return;
+            }
+            // Without FIFO, RBR is overwritten
+            self.rx.clear();
+        }
+        if self.rx.is_empty() {
+            self.lsr_errors |= errors;
+        }
+        self.rx.push_back((byte, errors));
+    }
+
+    fn read_rbr(&mut self) -> u8 {
+        self.timeout_pending = false;
+        match self.rx.pop_front() {
+            Some((byte, _)) => {
+                if let Some(&(_, errors)) = self.rx.front() {
+                    self.lsr_errors |= errors;
+                }
+                byte
+            }
+            None => 0,
+        }
+    }
+
+    fn write_thr(&mut self, value: u8) {
+        self.thre_pending = false;
+        if self.tx.len() < self.fifo_size() {
+            self.tx.push_back(value & self.word_mask());
+        }
+        if !self.hold_tx {
+            while self.shift_out().is_some() {}
+        }
+    }
+
+    /// Move one character out of the transmitter
+    fn shift_out(&mut self) -> Option<u8> {
+        let byte = self.tx.pop_front()?;
+        if self.loopback() {
+            self.receive(byte, 0);
+        } else {
+            self.wire.push_back(byte);
+        }
+        if self.tx.is_empty() {
+            self.thre_pending = true;
+        }
+        Some(byte)
+    }
+
+    fn lsr(&self) -> u8 {
+        let mut lsr = self.lsr_errors;
+        if !self.rx.is_empty() {
+            lsr |= LSR::DR.bits();
+        }
+        if self.tx.is_empty() {
+            lsr |= (LSR::THRE | LSR::DHRE).bits();
+        }
+        if self.fifo_enabled() && self.rx.iter().any(|&(_, errors)| errors != 0) {
+            lsr |= LSR::RFE.bits();
+        }
+        lsr
+    }
+
+    fn read_lsr(&mut self) -> u8 {
+        let lsr = self.lsr();
+        self.lsr_errors = 0;
+        lsr
+    }
+
+    fn read_msr(&mut self) -> u8 {
+        let msr = self.msr_lines() | self.msr_deltas;
+        self.msr_deltas = 0;
+        msr
+    }
+
+    /// Interrupt identification (IIR\[3:0\]) of the highest priority pending interrupt
+    fn interrupt_id(&self) -> u8 {
+        let ier = IER::from_bits_truncate(self.ier);
+        if ier.contains(IER::RLSI) && self.lsr_errors & LSR_ERRORS != 0 {
+            0b0110
+        } else if ier.contains(IER::RDAI) && self.rx.len() >= self.rx_trigger() {
+            0b0100
+        } else if ier.contains(IER::RDAI) && self.timeout_pending {
+            0b1100
+        } else if ier.contains(IER::THREI) && self.thre_pending {
+            0b0010
+        } else if ier.contains(IER::MSI) && self.msr_deltas & MSR_DELTAS != 0 {
+            0b0000
+        } else {
+            0b0001
+        }
+    }
+
+    fn read_iir(&mut self) -> u8 {
+        let id = self.interrupt_id();
+        if id == 0b0010 {
+            self.thre_pending = false;
+        }
+        let mut iir = id;
+        if self.fifo_enabled() {
+            iir |= 0b1100_0000;
+        }
+        if self.fifo_64byte() {
+            iir |= 0b0010_0000;
+        }
+        iir
+    }
+
+    fn write_ier(&mut self, value: u8) {
+        let value = value & self.chip.ier_mask();
+        if value & IER::THREI.bits() != 0 && self.ier & IER::THREI.bits() == 0 && self.tx.is_empty()
+        {
+            self.thre_pending = true;
+        }
+        self.ier = value;
+    }
+
+    fn write_fcr(&mut self, value: u8) {
+        if !self.chip.has_fifo() {
+            return;
+        }
+        let mut value = value;
+        if self.chip != Chip::U16750 || self.lcr & LCR_DLAB == 0 {
+            // FCR[5] can only be changed while DLAB is set
+            value = (value & !FCR_64BYTE) | (self.fcr & FCR_64BYTE);
+        }
+        if (value ^ self.fcr) & FCR_ENABLE != 0 {
+            self.rx.clear();
+            self.tx.clear();
+        }
+        if value & FCR_CLEAR_RX != 0 {
+            self.rx.clear();
+            self.timeout_pending = false;
+        }
+        if value & FCR_CLEAR_TX != 0 {
+            self.tx.clear();
+        }
+        self.fcr = value & !(FCR_CLEAR_RX | FCR_CLEAR_TX);
+    }
+
+    fn write_lcr(&mut self, value: u8) {
+        let ended_break = self.lcr & LCR_BREAK != 0 && value & LCR_BREAK == 0;
+        self.lcr = value;
+        if ended_break && self.loopback() {
+            self.receive(0, LSR::BI.bits());
+        }
+    }
+
+    fn write_mcr(&mut self, value: u8) {
+        let old = self.msr_lines();
+        self.mcr = value & self.chip.mcr_mask();
+        self.update_msr(old);
+    }
+
+    fn read(&mut self, offset: usize) -> u8 {
+        let dlab = self.lcr & LCR_DLAB != 0;
+        match offset {
+            offset::RBR if dlab => self.dll,
+            offset::RBR => self.read_rbr(),
+            offset::IER if dlab => self.dlh,
+            offset::IER => self.ier,
+            offset::IIR => self.read_iir(),
+            offset::LCR => self.lcr,
+            offset::MCR => self.mcr,
+            offset::LSR => self.read_lsr(),
+            offset::MSR => self.read_msr(),
+            offset::SCR if self.chip == Chip::U8250 => 0xff,
+            offset::SCR => self.scr,
+            _ => 0xff,
+        }
+    }
+
+    fn write(&mut self, offset: usize, value: u8) {
+        let dlab = self.lcr & LCR_DLAB != 0;
+        match offset {
+            offset::THR if dlab => self.dll = value,
+            offset::THR => self.write_thr(value),
+            offset::IER if dlab => self.dlh = value,
+            offset::IER => self.write_ier(value),
+            offset::FCR => self.write_fcr(value),
+            offset::LCR => self.write_lcr(value),
+            offset::MCR => self.write_mcr(value),
+            offset::SCR if self.chip != Chip::U8250 => self.scr = value,
+            _ => {}
+        }
+    }
+}
+
+/// # A behavioural model of an 8250 family UART
+///
+/// See the [module documentation](self) for an example.
+pub struct Model {
+    state: RefCell<State>,
+}
+
+impl Model {
+    /// Creates a model of the given chip in its reset state.
+    pub fn new(chip: Chip) -> Self {
+        Self {
+            state: RefCell::new(State::new(chip)),
+        }
+    }
+
+    /// Get the modelled chip
+    pub fn chip(&self) -> Chip {
+        self.state.borrow().chip
+    }
+
+    /// A character arrives on the line
+    pub fn receive(&self, byte: u8) {
+        self.state.borrow_mut().receive(byte, 0);
+    }
+
+    /// Several characters arrive on the line
+    pub fn receive_all(&self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.receive(byte);
+        }
+    }
+
+    /// A character with errors arrives on the line
+    pub fn receive_with_fault(&self, byte: u8, fault: RxFault) {
+        self.state.borrow_mut().receive(byte, fault.lsr());
+    }
+
+    /// A character with a parity error arrives on the line
+    pub fn receive_parity_error(&self, byte: u8) {
+        self.receive_with_fault(
+            byte,
+            RxFault {
+                parity: true,
+                ..RxFault::default()
+            },
+        );
+    }
+
+    /// A character with a framing error arrives on the line
+    pub fn receive_framing_error(&self, byte: u8) {
+        self.receive_with_fault(
+            byte,
+            RxFault {
+                framing: true,
+                ..RxFault::default()
+            },
+        );
+    }
+
+    /// The line is held in the spacing state for longer than a character, which loads a NUL
+    pub fn receive_break(&self) {
+        self.receive_with_fault(
+            0,
+            RxFault {
+                break_condition: true,
+                ..RxFault::default()
+            },
+        );
+    }
+
+    /// A glitch on the line is sampled as a garbled character
+    pub fn receive_noise(&self, byte: u8) {
+        self.receive_with_fault(
+            byte,
+            RxFault {
+                parity: true,
+                framing: true,
+                break_condition: false,
+            },
+        );
+    }
+
+    /// The line stays idle for the character timeout (4 character times)
+    ///
+    /// Raises the timeout interrupt if the receive FIFO holds data.
+    pub fn idle(&self) {
+        let mut state = self.state.borrow_mut();
+        if state.fifo_enabled() && !state.rx.is_empty() {
+            state.timeout_pending = true;
+        }
+    }
+
+    /// Number of characters waiting in the receiver
+    pub fn rx_len(&self) -> usize {
+        self.state.borrow().rx.len()
+    }
+
+    /// Keep written characters in the transmitter until [`Model::shift_out`] is called
+    pub fn hold_transmitter(&self, hold: bool) {
+        let mut state = self.state.borrow_mut();
+        state.hold_tx = hold;
+        if !hold {
+            while state.shift_out().is_some() {}
+        }
+    }
+
+    /// Send one held character, returning it
+    pub fn shift_out(&self) -> Option<u8> {
+        self.state.borrow_mut().shift_out()
+    }
+
+    /// Number of characters waiting in the transmitter
+    pub fn tx_len(&self) -> usize {
+        self.state.borrow().tx.len()
+    }
+
+    /// Take the next character which has been sent on the line
+    pub fn transmitted(&self) -> Option<u8> {
+        self.state.borrow_mut().wire.pop_front()
+    }
+
+    /// Set the modem status inputs (CTS, DSR, RI, DCD)
+    pub fn set_modem_inputs(&self, inputs: ModemInputs) {
+        let mut state = self.state.borrow_mut();
+        let old = state.msr_lines();
+        state.inputs = inputs;
+        state.update_msr(old);
+    }
+
+    /// Whether the transmitter is sending a break (LCR\[6\])
+    pub fn is_sending_break(&self) -> bool {
+        self.state.borrow().lcr & LCR_BREAK != 0
+    }
+
+    /// Whether the INTR output is asserted
+    pub fn interrupt(&self) -> bool {
+        self.state.borrow().interrupt_id() & 1 == 0
+    }
+
+    /// Current divisor latch value
+    pub fn divisor(&self) -> u16 {
+        let state = self.state.borrow();
+        u16::from_le_bytes([state.dll, state.dlh])
+    }
+
+    /// Current LCR, read without side effects
+    pub fn lcr(&self) -> u8 {
+        self.state.borrow().lcr
+    }
+
+    /// Current IER, read without side effects
+    pub fn ier(&self) -> IER {
+        IER::from_bits_truncate(self.state.borrow().ier)
+    }
+
+    /// Current MCR, read without side effects
+    pub fn mcr(&self) -> u8 {
+        self.state.borrow().mcr
+    }
+
+    /// Last value written to FCR, without the self-clearing bits
+    pub fn fcr(&self) -> u8 {
+        self.state.borrow().fcr
+    }
+
+    /// Current LSR, read without side effects
+    pub fn lsr(&self) -> LSR {
+        LSR::from_bits_truncate(self.state.borrow().lsr())
+    }
+}
+
+impl RegisterAccess for Model {
+    fn read(&self, offset: usize) -> u8 {
+        self.state.borrow_mut().read(offset)
+    }
+
+    fn write(&self, offset: usize, value: u8) {
+        self.state.borrow_mut().write(offset, value)
+    }
+}
@@ -0,0 +1,319 @@
+/*!
+# Chip identification
+
+[`Uart8250::probe`] works out which member of the 8250 family is behind the registers, the way
+Linux's `autoconfig` does: IER must hold what is written to it, IIR\[7:6\] tell how well the FIFOs
+work once FCR\[0\] is set, and the 16650, 16750 and OX16C950 are told apart by their extra
+registers. If loopback mode works, the FIFO depths are then measured by sending characters to
+the UART itself, so nothing goes out on the line.
+
+```no_run
+use uart8250::probe::ChipType;
+use uart8250::MmioUart8250;
+
+let uart: MmioUart8250 = unsafe { MmioUart8250::new(0x1000_0000) };
+let capabilities = uart.probe().unwrap();
+let uart = uart.with_divisor_variant(capabilities.chip.divisor_variant());
+if capabilities.chip == ChipType::U16550 {
+    // The FIFOs of the 16550 (without A) are broken
+}
+```
+*/
+
+use core::hint::spin_loop;
+
+use crate::access::RegisterAccess;
+use crate::registers::offset;
+use crate::uart::{LCR_8N1, LCR_DLAB};
+use crate::{BaudPlan, DivisorVariant, Error, Uart8250, FCR, LSR, MCR};
+
+/// Twice the FIFO of the OX16C950, so any FIFO overflows
+const MAX_FIFO_DEPTH: usize = 256;
+
+/// How many times to poll LSR while waiting for the transmitter
+const POLLS: usize = 1_000_000;
+
+/// Slow enough that the transmit FIFO can be filled before a character is sent
+const SLOW_DIVISOR: u16 = 0x400;
+
+/// LCR value which switches offset 2 to EFR
+const LCR_EFR: u8 = 0xbf;
+
+/// Members of the 8250 family
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum ChipType {
+    /// No FIFO and no scratch register
+    U8250,
+    /// No FIFO
+    U16450,
+    /// FIFOs which don't work
+    U16550,
+    /// 16-byte FIFOs
+    U16550A,
+    /// 32-byte FIFOs and EFR
+    U16650,
+    /// 64-byte FIFOs, selected with FCR\[5\] while DLAB is set
+    U16750,
+    /// Oxford Semiconductor OX16C950, 128-byte FIFOs and ICR
+    U16950,
+}
+
+impl ChipType {
+    /// FIFO depth from the data sheet, 1 if there is no working FIFO
+    pub const fn fifo_depth(self) -> usize {
+        match self {
+            ChipType::U8250 | ChipType::U16450 | ChipType::U16550 => 1,
+            ChipType::U16550A => 16,
+            ChipType::U16650 => 32,
+            ChipType::U16750 => 64,
+            ChipType::U16950 => 128,
+        }
+    }
+
+    /// Baud rate generator, for [`Uart8250::with_divisor_variant`]
+    pub const fn divisor_variant(self) -> DivisorVariant {
+        match self {
+            ChipType::U16950 => DivisorVariant::Ox16C950,
+            _ => DivisorVariant::Standard,
+        }
+    }
+}
+
+/// # What [`Uart8250::probe`] found
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct Capabilities {
+    pub chip: ChipType,
+    /// SCR keeps what is written to it
+    pub scratch: bool,
+    /// FIFOs work, IIR\[7:6\] both set after FCR\[0\]
+    pub fifo: bool,
+    /// EFR is there (16650 and later)
+    pub efr: bool,
+    /// Loopback mode (MCR\[4\]) works
+    pub loopback: bool,
+    /// Characters the transmitter holds besides its shift register
+    ///
+    /// Measured if there is a FIFO and loopback works, or else [`ChipType::fifo_depth`].
+    pub tx_fifo_depth: usize,
+    /// Characters the receiver holds, measured like `tx_fifo_depth`
+    pub rx_fifo_depth: usize,
+}
+
+impl<A: RegisterAccess> Uart8250<A> {
+    /// Identifies the UART and measures its FIFOs.
+    ///
+    /// LCR, MCR, IER, SCR, the divisor and the FIFO control are restored afterwards, and characters
+    /// in the FIFOs are lost.
+    ///
+    /// Returns [`Error::NotDetected`] if IER doesn't behave like that of a UART, or if the
+    /// transmitter doesn't finish while measuring the FIFOs.
+    pub fn probe(&self) -> Result<Capabilities, Error> {
+        let lcr = self.read_lcr();
+        self.write_lcr(0);
+        let ier = self.read_ier();
+        self.write_ier(0);
+        let cleared = self.read_ier() & 0b1111;
+        self.write_ier(0b1111);
+        let set = self.read_ier() & 0b1111;
+        self.write_ier(0);
+        if cleared != 0 || set != 0b1111 {
+            self.write_ier(ier);
+            self.write_lcr(lcr);
+            return Err(Error::NotDetected);
+        }
+
+        let mcr = self.read_mcr();
+        let scr = self.read_sr();
+        let iir = self.read_iir();
+        let shadow = self.fcr();
+        // The clock doesn't matter, only the divisor is written back
+        let previous = self.read_baud_plan(0);
+
+        let capabilities = self.identify(&previous);
+
+        self.write_baud_plan(&previous);
+        // FCR can't be read, so the FIFOs are set up from what IIR said before, and the rest from
+        // the driver's copy
+        let mut fcr = (shadow & (FCR::RX_TRIGGER_MSB | FCR::RX_TRIGGER_LSB | FCR::DMA_MODE))
+            | FCR::CLEAR_RX
+            | FCR::CLEAR_TX;
+        if iir & 0b1000_0000 != 0 {
+            fcr |= FCR::ENABLE;
+        }
+        if iir & 0b0010_0000 != 0 {
+            fcr |= FCR::FIFO_64BYTE;
+        }
+        self.write_lcr(LCR_DLAB);
+        self.set_fcr(fcr);
+        self.write_lcr(lcr);
+        self.write_mcr(mcr);
+        self.write_sr(scr);
+        self.write_ier(ier);
+        capabilities
+    }
+
+    /// Tell the chips apart, with IER cleared
+    fn identify(&self, plan: &BaudPlan) -> Result<Capabilities, Error> {
+        self.write_sr(0xa5);
+        let first = self.read_sr();
+        self.write_sr(0x5a);
+        let scratch = first == 0xa5 && self.read_sr() == 0x5a;
+
+        // In loopback, RTS and OUT2 come back as CTS and DCD
+        self.set_mcr(MCR::RTS | MCR::OUT2 | MCR::LOOPBACK);
+        let loopback = self.read_msr() & 0xf0 == 0x90;
+        self.write_mcr(0);
+
+        self.write_lcr(0);
+        self.set_fcr(FCR::ENABLE);
+        let iir = self.read_iir();
+        let (chip, efr) = match iir >> 6 {
+            0b00 if scratch => (ChipType::U16450, false),
+            0b00 => (ChipType::U8250, false),
+            0b10 => (ChipType::U16550, false),
+            0b11 => self.identify_16550a(),
+            _ => {
+                return Err(Error::UnexpectedRegisterValue {
+                    offset: offset::IIR,
+                    value: iir,
+                })
+            }
+        };
+        let fifo = iir >> 6 == 0b11;
+
+        let (tx_fifo_depth, rx_fifo_depth) = if fifo && loopback {
+            self.measure_fifo_depth(chip, plan)?
+        } else {
+            (chip.fifo_depth(), chip.fifo_depth())
+        };
+        Ok(Capabilities {
+            chip,
+            scratch,
+            fifo,
+            efr,
+            loopback,
+            tx_fifo_depth,
+            rx_fifo_depth,
+        })
+    }
+
+    /// Tell apart the chips with working FIFOs, returning whether there is an EFR
+    fn identify_16550a(&self) -> (ChipType, bool) {
+        self.write_lcr(LCR_EFR);
+        if self.read_efr() == 0 {
+            // EFR[4] enables the enhanced functions, including ICR
+            self.write_efr(0b0001_0000);
+            self.write_lcr(0);
+            let id = [
+                self.read_icr(offset::icr::ID1),
+                self.read_icr(offset::icr::ID2),
+                self.read_icr(offset::icr::ID3),
+            ];
+            self.write_lcr(LCR_EFR);
+            self.write_efr(0);
+            self.write_lcr(0);
+            let chip = if id[0] == 0x16 && id[1] == 0xc9 && id[2] & 0xf0 == 0x50 {
+                ChipType::U16950
+            } else {
+                ChipType::U16650
+            };
+            return (chip, true);
+        }
+
+        // The 16750 only changes FCR[5] while DLAB is set, which shows in IIR[5]
+        self.write_lcr(LCR_DLAB);
+        self.set_fcr(FCR::ENABLE);
+        self.write_lcr(0);
+        self.set_fcr(FCR::FIFO_64BYTE | FCR::ENABLE);
+        let without_dlab = self.read_iir() >> 5;
+        self.write_lcr(LCR_DLAB);
+        self.set_fcr(FCR::FIFO_64BYTE | FCR::ENABLE);
+        let with_dlab = self.read_iir() >> 5;
+        self.set_fcr(FCR::ENABLE);
+        self.write_lcr(0);
+        if without_dlab == 0b110 && with_dlab == 0b111 {
+            (ChipType::U16750, false)
+        } else {
+            (ChipType::U16550A, false)
+        }
+    }
+
+    /// Measure the transmit and receive FIFOs in loopback mode, returning their depths
+    fn measure_fifo_depth(&self, chip: ChipType, plan: &BaudPlan) -> Result<(usize, usize), Error> {
+        let fcr = match chip {
+            ChipType::U16750 => FCR::FIFO_64BYTE | FCR::CLEAR_TX | FCR::CLEAR_RX | FCR::ENABLE,
+            _ => FCR::CLEAR_TX | FCR::CLEAR_RX | FCR::ENABLE,
+        };
+        self.write_lcr(LCR_DLAB | LCR_8N1);
+        self.set_fcr(fcr);
+        self.write_lcr(LCR_8N1);
+        self.set_mcr(MCR::LOOPBACK);
+        let fast = BaudPlan {
+            divisor: 1,
+            ..*plan
+        };
+        let slow = BaudPlan {
+            divisor: SLOW_DIVISOR,
+            ..*plan
+        };
+
+        // Send one character at a time until the receiver overruns
+        self.write_baud_plan(&fast);
+        for _ in 0..MAX_FIFO_DEPTH {
+            self.write_thr(0);
+            if self.wait_for(LSR::DHRE)?.contains(LSR::OE) {
+                break;
+            }
+        }
+        let rx_fifo_depth = self.drain();
+
+        // Once the first character is in the shift register, the rest fill the FIFO and are
+        // dropped when it is full
+        self.write_baud_plan(&slow);
+        self.set_fcr(fcr);
+        self.write_thr(0);
+        self.wait_for(LSR::THRE)?;
+        for _ in 0..MAX_FIFO_DEPTH {
+            self.write_thr(0);
+        }
+        self.write_baud_plan(&fast);
+        let mut received = 0;
+        for _ in 0..POLLS {
+            let lsr = self.raw_lsr();
+            if lsr.contains(LSR::DR) {
+                self.read_rbr();
+                received += 1;
+            } else if lsr.contains(LSR::DHRE) {
+                // Less the character which was in the shift register
+                let tx_fifo_depth = (received + self.drain()).saturating_sub(1).max(1);
+                return Ok((tx_fifo_depth, rx_fifo_depth));
+            }
+            spin_loop();
+        }
+        Err(Error::NotDetected)
+    }
+
+    /// Poll LSR until `flag` is set, returning all the bits seen meanwhile
+    fn wait_for(&self, flag: LSR) -> Result<LSR, Error> {
+        let mut seen = LSR::empty();
+        for _ in 0..POLLS {
+            let lsr = self.raw_lsr();
+            seen |= lsr;
+            if lsr.contains(flag) {
+                return Ok(seen);
+            }
+            spin_loop();
+        }
+        Err(Error::NotDetected)
+    }
+
+    /// Read all received characters, returning how many there were
+    fn drain(&self) -> usize {
+        let mut count = 0;
+        while count <= MAX_FIFO_DEPTH && self.raw_lsr().contains(LSR::DR) {
+            self.read_rbr();
+            count += 1;
+        }
+        count
+    }
+}
@@ -1,5 +1,3 @@
-use core::u8;
-
 use volatile_register::{RO, RW};
 
 /// # UART Registers
@@ -36,7 +34,72 @@ pub struct Registers {
 
 impl Registers {
     /// Constructs a new instance of the UART registers starting at the given base address.
-    pub fn from_base_address(base_address: usize) -> &'static mut Self {
-        unsafe { &mut *(base_address as *mut crate::registers::Registers) }
+    ///
+    /// # Safety
+    ///
+    /// `base_address` must be the base address of the registers of an 8250 compatible UART,
+    /// mapped for the rest of the program, and nothing else may access them while the returned
+    /// reference exists.
+    #[deprecated(note = "Use `access::Mmio`, which doesn't hold a reference to the registers")]
+    pub unsafe fn from_base_address(base_address: usize) -> &'static mut Self {
+        &mut *(base_address as *mut Self)
+    }
+}
+
+/// Register offsets, counted in registers rather than bytes.
+///
+/// Several registers share an offset and are told apart by the access direction or by DLAB.
+pub mod offset {
+    /// Receiver Buffer (read, DLAB = 0)
+    pub const RBR: usize = 0;
+    /// Transmitter Holding Buffer (write, DLAB = 0)
+    pub const THR: usize = 0;
+    /// Divisor Latch Low Byte (DLAB = 1)
+    pub const DLL: usize = 0;
+    /// Interrupt Enable Register (DLAB = 0)
+    pub const IER: usize = 1;
+    /// Divisor Latch High Byte (DLAB = 1)
+    pub const DLH: usize = 1;
+    /// Interrupt Identification Register (read)
+    pub const IIR: usize = 2;
+    /// FIFO Control Register (write)
+    pub const FCR: usize = 2;
+    /// Line Control Register
+    pub const LCR: usize = 3;
+    /// Modem Control Register
+    pub const MCR: usize = 4;
+    /// Line Status Register
+    pub const LSR: usize = 5;
+    /// Modem Status Register
+    pub const MSR: usize = 6;
+    /// Scratch Register
+    pub const SCR: usize = 7;
+    /// Enhanced Feature Register of the 16650 and later (LCR = 0xbf)
+    pub const EFR: usize = 2;
+    /// OX16C950 Indexed Control Register (write, LCR != 0xbf), selected by SCR
+    pub const ICR: usize = 5;
+    /// NXP LPC Fractional Divider Register
+    pub const LPC_FDR: usize = 0x0a;
+    /// MediaTek high speed mode
+    pub const MTK_HIGHSPEED: usize = 0x09;
+    /// MediaTek sample count in high speed mode 3
+    pub const MTK_SAMPLE_COUNT: usize = 0x0a;
+    /// MediaTek sample point in high speed mode 3
+    pub const MTK_SAMPLE_POINT: usize = 0x0b;
+    /// DesignWare Divisor Latch Fraction Register
+    pub const DW_DLF: usize = 0x30;
+
+    /// Indices of the OX16C950 registers reached through [`ICR`]
+    pub mod icr {
+        /// Additional Control Register
+        pub const ACR: u8 = 0x00;
+        /// Times Clock Register
+        pub const TCR: u8 = 0x02;
+        /// Identification Register 1, 0x16
+        pub const ID1: u8 = 0x08;
+        /// Identification Register 2, 0xc9
+        pub const ID2: u8 = 0x09;
+        /// Identification Register 3, 0x5x
+        pub const ID3: u8 = 0x0a;
     }
 }
@@ -0,0 +1,362 @@
+/*!
+# Loopback self-test
+
+[`Uart8250::self_test`] checks a UART without anything connected to it, using loopback mode
+(MCR\[4\]), where the transmitter is wired to the receiver and the modem control outputs to the
+modem status inputs inside the UART. It sends test patterns at every word length and parity,
+provokes an overrun and a break, checks the modem line mapping and raises each interrupt.
+
+```no_run
+use uart8250::MmioUart8250;
+
+let uart: MmioUart8250 = unsafe { MmioUart8250::new(0x1000_0000) };
+uart.init(1_843_200, 115200);
+
+let report = uart.self_test();
+for failure in report.failures() {
+    // e.g. log it
+    let _ = failure;
+}
+assert!(report.passed());
+```
+*/
+
+use core::hint::spin_loop;
+
+use crate::access::RegisterAccess;
+use crate::uart::{LCR_8N1, LCR_BREAK, LCR_DLAB, LINE_ERRORS};
+use crate::{FifoConfig, InterruptType, LineConfig, Parity, Uart8250, FCR, IER, LSR, MCR, MSR};
+
+/// Sent at every word length and parity, cut down to the word length
+pub const PATTERNS: [u8; 4] = [0x00, 0xff, 0x55, 0xaa];
+
+/// Parities tested, in the order of [`SelfTestReport::data`]
+pub const PARITIES: [Parity; 5] = [
+    Parity::No,
+    Parity::Odd,
+    Parity::Even,
+    Parity::Mark,
+    Parity::Space,
+];
+
+/// Modem control outputs and the modem status inputs they are looped back to, in the order of
+/// [`SelfTestReport::modem_lines`]
+pub const MODEM_LINES: [(MCR, MSR); 4] = [
+    (MCR::RTS, MSR::CTS),
+    (MCR::DTR, MSR::DSR),
+    (MCR::OUT1, MSR::RI),
+    (MCR::OUT2, MSR::CD),
+];
+
+/// Interrupts raised, in the order of [`SelfTestReport::interrupts`]
+pub const INTERRUPTS: [InterruptType; 4] = [
+    InterruptType::ReceivedDataAvailable,
+    InterruptType::TransmitterHoldingRegisterEmpty,
+    InterruptType::ReceiverLineStatus,
+    InterruptType::ModemStatus,
+];
+
+/// How many times to poll LSR while waiting for a character
+const POLLS: usize = 1_000_000;
+
+/// What went wrong in a check
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Failure {
+    /// `sent` came back as `received`, or not at all
+    Data { sent: u8, received: Option<u8> },
+    /// The LSR error bits were `actual`, where `expected` were wanted
+    LineStatus { expected: LSR, actual: LSR },
+    /// With `mcr` set, the modem status inputs were `actual` instead of `expected`
+    ModemLines {
+        mcr: MCR,
+        expected: MSR,
+        actual: MSR,
+    },
+    /// IIR identified `actual` instead of `expected`
+    Interrupt {
+        expected: InterruptType,
+        actual: Option<InterruptType>,
+    },
+}
+
+/// Outcome of a check
+pub type Check = Result<(), Failure>;
+
+/// # What [`Uart8250::self_test`] found
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct SelfTestReport {
+    /// [`PATTERNS`] sent with 5 to 8 data bits, and each of [`PARITIES`]
+    pub data: [[Check; 5]; 4],
+    /// A character received while RBR is full sets LSR\[1\]
+    pub overrun: Check,
+    /// A break sets LSR\[4\] and is received as NUL
+    pub break_detect: Check,
+    /// Each of [`MODEM_LINES`]
+    pub modem_lines: [Check; 4],
+    /// Each of [`INTERRUPTS`]
+    pub interrupts: [Check; 4],
+}
+
+impl SelfTestReport {
+    /// Whether every check passed
+    pub fn passed(&self) -> bool {
+        self.failures().next().is_none()
+    }
+
+    /// The failed checks
+    pub fn failures(&self) -> impl Iterator<Item = Failure> + '_ {
+        self.data
+            .iter()
+            .flatten()
+            .chain([&self.overrun, &self.break_detect])
+            .chain(&self.modem_lines)
+            .chain(&self.interrupts)
+            .filter_map(|check| check.err())
+    }
+}
+
+impl<A: RegisterAccess> Uart8250<A> {
+    /// Tests the UART in loopback mode, at its current baud rate.
+    ///
+    /// The FIFOs are disabled meanwhile. LCR, MCR, IER and the FIFO configuration are restored
+    /// afterwards, see [`fifo_config`](Self::fifo_config), and characters received meanwhile are
+    /// lost. The interrupt output is driven while interrupts are checked, so the interrupt should
+    /// be masked at the interrupt controller.
+    pub fn self_test(&self) -> SelfTestReport {
+        let lcr = self.read_lcr();
+        let mcr = self.mcr();
+        let ier = self.ier();
+        let fifo = self.fifo_config();
+
+        self.set_ier(IER::empty());
+        self.write_lcr(lcr & !(LCR_DLAB | LCR_BREAK));
+        self.set_fcr(FCR::empty());
+        self.set_mcr(MCR::LOOPBACK);
+        self.discard_received();
+
+        let mut data = [[Ok(()); 5]; 4];
+        for (data_bits, row) in (5..=8).zip(data.iter_mut()) {
+            for (&parity, check) in PARITIES.iter().zip(row.iter_mut()) {
+                *check = self.check_patterns(data_bits, parity);
+            }
+        }
+
+        self.write_lcr(LCR_8N1);
+        let overrun = self.check_overrun();
+        let break_detect = self.check_break();
+        let modem_lines = MODEM_LINES.map(|(output, input)| self.check_modem_line(output, input));
+        let interrupts = INTERRUPTS.map(|interrupt| self.check_interrupt(interrupt));
+
+        self.discard_received();
+        self.set_mcr(mcr);
+        self.read_msr();
+        self.write_lcr(lcr);
+        self.set_fifo_config(&FifoConfig {
+            clear_rx: true,
+            clear_tx: true,
+            ..fifo
+        });
+        self.set_ier(ier);
+        SelfTestReport {
+            data,
+            overrun,
+            break_detect,
+            modem_lines,
+            interrupts,
+        }
+    }
+
+    fn check_patterns(&self, data_bits: u8, parity: Parity) -> Check {
+        let config = LineConfig {
+            data_bits,
+            parity,
+            ..LineConfig::new(0)
+        };
+        // Every combination tested is valid
+        self.write_lcr(config.lcr().unwrap_or_default());
+        let mask = 0xff >> (8 - data_bits);
+        for pattern in PATTERNS {
+            let sent = pattern & mask;
+            self.write_thr(sent);
+            let (lsr, received) = self.receive();
+            if received != Some(sent) {
+                return Err(Failure::Data { sent, received });
+            }
+            let errors = lsr & LINE_ERRORS;
+            if !errors.is_empty() {
+                return Err(Failure::LineStatus {
+                    expected: LSR::empty(),
+                    actual: errors,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Without FIFOs, the second character overwrites the first
+    fn check_overrun(&self) -> Check {
+        self.write_thr(0x55);
+        let mut seen = self.wait_for_idle();
+        self.write_thr(0xaa);
+        seen |= self.wait_for_idle();
+        let (lsr, _) = self.receive();
+        expect_errors(LSR::OE, seen | lsr)
+    }
+
+    fn check_break(&self) -> Check {
+        self.write_lcr(LCR_BREAK | LCR_8N1);
+        // Hold the break for two characters
+        let mut seen = LSR::empty();
+        for _ in 0..2 {
+            self.write_thr(0xff);
+            seen |= self.wait_for_idle();
+        }
+        self.write_lcr(LCR_8N1);
+
+        // The break is received as NUL, after anything else
+        let mut last = None;
+        for _ in 0..POLLS {
+            let lsr = self.raw_lsr();
+            seen |= lsr;
+            if lsr.contains(LSR::DR) {
+                last = Some(self.read_rbr());
+            } else if seen.contains(LSR::BI) {
+                break;
+            }
+            spin_loop();
+        }
+        expect_errors(LSR::BI, seen)?;
+        match last {
+            Some(0) => Ok(()),
+            received => Err(Failure::Data { sent: 0, received }),
+        }
+    }
+
+    fn check_modem_line(&self, output: MCR, input: MSR) -> Check {
+        let mcr = MCR::LOOPBACK | output;
+        self.set_mcr(mcr);
+        let actual = self.msr() & (MSR::CTS | MSR::DSR | MSR::RI | MSR::CD);
+        self.set_mcr(MCR::LOOPBACK);
+        self.read_msr();
+        if actual == input {
+            Ok(())
+        } else {
+            Err(Failure::ModemLines {
+                mcr,
+                expected: input,
+                actual,
+            })
+        }
+    }
+
+    fn check_interrupt(&self, expected: InterruptType) -> Check {
+        self.wait_for_idle();
+        self.discard_received();
+        self.read_msr();
+        // Clears a pending THRE interrupt
+        self.read_iir();
+        let actual = match expected {
+            InterruptType::ReceivedDataAvailable => {
+                self.set_ier(IER::RDAI);
+                self.write_thr(0x55);
+                self.wait_for_interrupt()
+            }
+            InterruptType::TransmitterHoldingRegisterEmpty => {
+                self.set_ier(IER::THREI);
+                self.wait_for_interrupt()
+            }
+            InterruptType::ReceiverLineStatus => {
+                self.set_ier(IER::RLSI);
+                self.write_thr(0x55);
+                self.wait_for_idle();
+                // Reading LSR would clear the overrun, so only IIR is polled
+                self.write_thr(0xaa);
+                self.wait_for_interrupt()
+            }
+            _ => {
+                self.set_ier(IER::MSI);
+                self.set_mcr(MCR::LOOPBACK | MCR::RTS);
+                let actual = self.wait_for_interrupt();
+                self.set_mcr(MCR::LOOPBACK);
+                actual
+            }
+        };
+        self.set_ier(IER::empty());
+        self.discard_received();
+        self.read_msr();
+        if actual == Some(expected) {
+            Ok(())
+        } else {
+            Err(Failure::Interrupt { expected, actual })
+        }
+    }
+
+    /// Wait for a character, returning the LSR bits seen meanwhile and the character
+    fn receive(&self) -> (LSR, Option<u8>) {
+        let lsr = self.receive_ready();
+        if lsr.contains(LSR::DR) {
+            (lsr, Some(self.read_rbr()))
+        } else {
+            (lsr, None)
+        }
+    }
+
+    /// Poll LSR until a character is received, returning the bits seen meanwhile
+    fn receive_ready(&self) -> LSR {
+        let mut seen = LSR::empty();
+        for _ in 0..POLLS {
+            let lsr = self.raw_lsr();
+            seen |= lsr;
+            if lsr.contains(LSR::DR) {
+                break;
+            }
+            spin_loop();
+        }
+        seen
+    }
+
+    /// Poll LSR until the transmitter is idle, returning the bits seen meanwhile
+    fn wait_for_idle(&self) -> LSR {
+        let mut seen = LSR::empty();
+        for _ in 0..POLLS {
+            let lsr = self.raw_lsr();
+            seen |= lsr;
+            if lsr.contains(LSR::DHRE) {
+                break;
+            }
+            spin_loop();
+        }
+        seen
+    }
+
+    /// Poll IIR until an interrupt is pending
+    fn wait_for_interrupt(&self) -> Option<InterruptType> {
+        for _ in 0..POLLS {
+            if let Some(interrupt) = self.read_interrupt_type() {
+                return Some(interrupt);
+            }
+            spin_loop();
+        }
+        None
+    }
+
+    fn discard_received(&self) {
+        for _ in 0..POLLS {
+            if !self.raw_lsr().contains(LSR::DR) {
+                break;
+            }
+            self.read_rbr();
+        }
+    }
+}
+
+fn expect_errors(expected: LSR, lsr: LSR) -> Check {
+    if lsr.contains(expected) {
+        Ok(())
+    } else {
+        Err(Failure::LineStatus {
+            expected,
+            actual: lsr & LINE_ERRORS,
+        })
+    }
+}
@@ -0,0 +1,156 @@
+/*!
+# Receive and transmit halves
+
+[`Uart8250::split`] borrows the UART as an [`Rx`] and a [`Tx`], which can be handed to
+different tasks, threads or interrupt handlers:
+
+```
+use uart8250::model::{Chip, Model};
+use uart8250::Uart8250;
+
+let mut uart = Uart8250::from_access(Model::new(Chip::U16550A));
+uart.init(1_843_200, 115200);
+uart.enable_loopback_mode();
+
+let (rx, tx) = uart.split();
+std::thread::scope(|scope| {
+    scope.spawn(move || {
+        for &byte in b"hello" {
+            while !tx.is_ready() {}
+            tx.write_byte(byte);
+        }
+    });
+    let mut received = Vec::new();
+    while received.len() < 5 {
+        if let Some(byte) = rx.read_byte().unwrap() {
+            received.push(byte);
+        }
+    }
+    assert_eq!(received, b"hello");
+});
+```
+
+[`Rx`] only reads RBR and [`Tx`] only writes THR. Each owns its own IER bits: RDAI and RLSI for
+[`Rx`], THREI for [`Tx`]. Both read LSR, and reading LSR clears its error bits, so the error bits
+[`Tx`] reads are kept for [`Rx`].
+
+Every register access of the halves is made in a critical section, so the halves never use the
+register access at the same time. The rest of the UART, e.g. the line settings, is only
+reachable again once both halves are dropped.
+*/
+
+use crate::access::RegisterAccess;
+use crate::line_break::RxEvent;
+use crate::{LineError, Uart8250, IER, LSR};
+
+impl<A: RegisterAccess> Uart8250<A> {
+    /// Splits the UART into its receive and transmit halves
+    pub fn split(&mut self) -> (Rx<'_, A>, Tx<'_, A>) {
+        (Rx { uart: self }, Tx { uart: self })
+    }
+}
+
+/// # The receive half of an 8250 UART
+///
+/// See the [module documentation](self).
+pub struct Rx<'a, A: RegisterAccess> {
+    uart: &'a Uart8250<A>,
+}
+
+// Every register access is made in a critical section, see the module documentation.
+unsafe impl<A: RegisterAccess + Send> Send for Rx<'_, A> {}
+
+impl<A: RegisterAccess> Rx<'_, A> {
+    /// Reads the next received character, if there is one
+    ///
+    /// A line error is returned before the character it came with, which is returned by the next
+    /// read.
+    pub fn read_byte(&self) -> Result<Option<u8>, LineError> {
+        critical_section::with(|_| {
+            let lsr = self.uart.raw_lsr() | self.uart.take_rx_errors();
+            if let Some(error) = LineError::from_lsr(lsr) {
+                Err(error)
+            } else if lsr.contains(LSR::DR) {
+                Ok(Some(self.uart.read_rbr()))
+            } else {
+                Ok(None)
+            }
+        })
+    }
+
+    /// Reads the next received character together with its LSR bits, see
+    /// [`Uart8250::read_event`]
+    pub fn read_event(&self) -> Option<RxEvent> {
+        critical_section::with(|_| {
+            let lsr = self.uart.raw_lsr() | self.uart.take_rx_errors();
+            if lsr.contains(LSR::DR) {
+                Some(RxEvent::new(self.uart.read_rbr(), lsr))
+            } else {
+                // The errors go with the next character
+                self.uart.keep_rx_errors(lsr);
+                None
+            }
+        })
+    }
+
+    /// get whether a character is waiting (LSR\[0\])
+    pub fn is_ready(&self) -> bool {
+        critical_section::with(|_| {
+            let lsr = self.uart.raw_lsr();
+            self.uart.keep_rx_errors(lsr);
+            lsr.contains(LSR::DR)
+        })
+    }
+
+    /// Enables the received data available and receiver line status interrupts (IER\[2, 0\])
+    pub fn enable_interrupt(&self) {
+        self.uart.modify_ier(|ier| ier | IER::RDAI | IER::RLSI);
+    }
+
+    /// Disables the received data available and receiver line status interrupts (IER\[2, 0\])
+    pub fn disable_interrupt(&self) {
+        self.uart.modify_ier(|ier| ier & !(IER::RDAI | IER::RLSI));
+    }
+}
+
+/// # The transmit half of an 8250 UART
+///
+/// See the [module documentation](self).
+pub struct Tx<'a, A: RegisterAccess> {
+    uart: &'a Uart8250<A>,
+}
+
+// Every register access is made in a critical section, see the module documentation.
+unsafe impl<A: RegisterAccess + Send> Send for Tx<'_, A> {}
+
+impl<A: RegisterAccess> Tx<'_, A> {
+    /// Write a character to THR, without checking whether it is empty
+    pub fn write_byte(&self, byte: u8) {
+        critical_section::with(|_| self.uart.write_thr(byte))
+    }
+
+    /// get whether THR can take another character (LSR\[5\])
+    pub fn is_ready(&self) -> bool {
+        self.lsr().contains(LSR::THRE)
+    }
+
+    /// get whether everything written has been sent (LSR\[6\])
+    pub fn is_idle(&self) -> bool {
+        self.lsr().contains(LSR::DHRE)
+    }
+
+    /// Read LSR, keeping its error bits for [`Rx`]
+    fn lsr(&self) -> LSR {
+        self.uart.tx_lsr()
+    }
+
+    /// Enables the transmitter holding register empty interrupt (IER\[1\])
+    pub fn enable_interrupt(&self) {
+        self.uart.modify_ier(|ier| ier | IER::THREI);
+    }
+
+    /// Disables the transmitter holding register empty interrupt (IER\[1\])
+    pub fn disable_interrupt(&self) {
+        self.uart.modify_ier(|ier| ier & !IER::THREI);
+    }
+}
@@ -0,0 +1,124 @@
+use crate::{ChipFifoInfo, InterruptType, LineError, LSR};
+
+/// # LSR, read once
+///
+/// Reading LSR clears its error bits, so checking them one read at a time loses all but the
+/// first. Get this with [`Uart8250::line_status`](crate::Uart8250::line_status) to check them
+/// all.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct LineStatus(LSR);
+
+impl LineStatus {
+    /// Get LSR bitflags
+    pub fn lsr(self) -> LSR {
+        self.0
+    }
+
+    /// Get the most significant receive error, if any, see [`LineError::from_lsr`]
+    pub fn error(self) -> Option<LineError> {
+        LineError::from_lsr(self.0)
+    }
+
+    /// get whether a character is waiting (LSR\[0\])
+    pub fn is_data_ready(self) -> bool {
+        self.0.contains(LSR::DR)
+    }
+
+    /// get whether a character was lost (LSR\[1\])
+    pub fn is_overrun_error(self) -> bool {
+        self.0.contains(LSR::OE)
+    }
+
+    /// get whether the character had the wrong parity (LSR\[2\])
+    pub fn is_parity_error(self) -> bool {
+        self.0.contains(LSR::PE)
+    }
+
+    /// get whether the character had no valid stop bit (LSR\[3\])
+    pub fn is_framing_error(self) -> bool {
+        self.0.contains(LSR::FE)
+    }
+
+    /// get whether a break was received (LSR\[4\])
+    pub fn is_break_interrupt(self) -> bool {
+        self.0.contains(LSR::BI)
+    }
+
+    /// get whether transmitter holding register is empty (LSR\[5\])
+    pub fn is_transmitter_holding_register_empty(self) -> bool {
+        self.0.contains(LSR::THRE)
+    }
+
+    /// get whether data holding registers are empty (LSR\[6\])
+    pub fn is_data_holding_registers_empty(self) -> bool {
+        self.0.contains(LSR::DHRE)
+    }
+
+    /// get whether there is an error in received FIFO (LSR\[7\])
+    pub fn is_received_fifo_error(self) -> bool {
+        self.0.contains(LSR::RFE)
+    }
+}
+
+impl From<LSR> for LineStatus {
+    fn from(lsr: LSR) -> Self {
+        Self(lsr)
+    }
+}
+
+/// # IIR, read once
+///
+/// Reading IIR clears a THRE interrupt it reports, so reading it again to check another field
+/// loses it. Get this with [`Uart8250::interrupt_id`](crate::Uart8250::interrupt_id) to check them
+/// all.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct InterruptId(u8);
+
+impl InterruptId {
+    /// Get the IIR value
+    pub fn bits(self) -> u8 {
+        self.0
+    }
+
+    /// get whether interrupt is pending (IIR\[0\] is 0)
+    pub fn is_pending(self) -> bool {
+        self.0 & 1 == 0
+    }
+
+    /// Get the pending interrupt type (IIR\[3:1\]), if any
+    ///
+    /// A [`InterruptType::TransmitterHoldingRegisterEmpty`] was cleared by the read, so it won't
+    /// be reported again.
+    pub fn interrupt_type(self) -> Option<InterruptType> {
+        match self.0 & 0b0000_1111 {
+            0b0000 => Some(InterruptType::ModemStatus),
+            0b0010 => Some(InterruptType::TransmitterHoldingRegisterEmpty),
+            0b0100 => Some(InterruptType::ReceivedDataAvailable),
+            0b0110 => Some(InterruptType::ReceiverLineStatus),
+            0b1100 => Some(InterruptType::Timeout),
+            0b1000 | 0b1010 | 0b1110 => Some(InterruptType::Reserved),
+            _ => None,
+        }
+    }
+
+    /// Get FIFO status (IIR\[7:6\])
+    pub fn fifo_status(self) -> ChipFifoInfo {
+        match self.0 & 0b1100_0000 {
+            0 => ChipFifoInfo::NoFifo,
+            0b0100_0000 => ChipFifoInfo::Reserved,
+            0b1000_0000 => ChipFifoInfo::EnabledNoFunction,
+            _ => ChipFifoInfo::Enabled,
+        }
+    }
+
+    /// get whether 64 Byte fifo (16750 only) is enabled (IIR\[5\])
+    pub fn is_64byte_fifo_enabled(self) -> bool {
+        self.0 & 0b0010_0000 != 0
+    }
+}
+
+impl From<u8> for InterruptId {
+    fn from(iir: u8) -> Self {
+        Self(iir)
+    }
+}
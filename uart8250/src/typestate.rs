@@ -0,0 +1,302 @@
+/*!
+# Typestate API
+
+[`Uart`] wraps a [`Uart8250`] and tracks in its type whether the line has been configured, so
+the data path is only reachable when it is safe to use:
+
+```text
+Unconfigured --configure--> Configured
+```
+
+DLAB is only set within [`Uart::with_divisor_latch`], which clears it again before returning.
+
+The raw register API stays available on [`Uart8250`], and [`Uart::into_inner`] goes back to it.
+
+```
+use uart8250::model::{Chip, Model};
+use uart8250::typestate::Uart;
+use uart8250::Uart8250;
+
+let model = Model::new(Chip::U16550A);
+let uart = Uart::new(Uart8250::from_access(&model)).configure(1_843_200, 115200);
+uart.write_byte(b'a');
+
+// Change the baud rate; the closure only gets the divisor registers
+uart.with_divisor_latch(|latch| latch.write_divisor(12));
+uart.write_byte(b'b');
+```
+
+Characters can only be written once the line is configured:
+
+```compile_fail
+use uart8250::model::{Chip, Model};
+use uart8250::typestate::Uart;
+use uart8250::Uart8250;
+
+let model = Model::new(Chip::U16550A);
+Uart::new(Uart8250::from_access(&model)).write_byte(b'a');
+```
+*/
+
+#[cfg(feature = "fmt")]
+use core::fmt;
+use core::marker::PhantomData;
+use core::time::Duration;
+
+use crate::access::RegisterAccess;
+use crate::dispatch::InterruptHandler;
+use crate::divisor_latch::Latch;
+use crate::line_break::{Delay, RxEvent};
+use crate::{
+    ChipFifoInfo, Error, FifoConfig, InterruptId, InterruptType, LineConfig, LineStatus, Parity,
+    Uart8250, IER, LSR, MCR, MSR,
+};
+
+/// The line settings are unknown
+pub struct Unconfigured;
+
+/// The line is configured and DLAB is clear
+pub struct Configured;
+
+/// # An 8250 UART whose state is tracked in its type
+pub struct Uart<A: RegisterAccess, S> {
+    uart: Uart8250<A>,
+    _state: PhantomData<S>,
+}
+
+impl<A: RegisterAccess, S> Uart<A, S> {
+    fn into_state<T>(self) -> Uart<A, T> {
+        Uart {
+            uart: self.uart,
+            _state: PhantomData,
+        }
+    }
+
+    /// Get back the raw register API
+    pub fn into_inner(self) -> Uart8250<A> {
+        self.uart
+    }
+}
+
+impl<A: RegisterAccess> Uart<A, Unconfigured> {
+    pub fn new(uart: Uart8250<A>) -> Self {
+        Self {
+            uart,
+            _state: PhantomData,
+        }
+    }
+
+    /// Configure the line with [`Uart8250::init`]
+    pub fn configure(self, clock: usize, baud_rate: usize) -> Uart<A, Configured> {
+        self.uart.init(clock, baud_rate);
+        self.into_state()
+    }
+
+    /// Configure the line with [`Uart8250::apply_line_config`]
+    pub fn configure_line(self, clock: usize, config: &LineConfig) -> Uart<A, Configured> {
+        self.uart.apply_line_config(clock, config);
+        self.into_state()
+    }
+}
+
+impl<A: RegisterAccess> Uart<A, Configured> {
+    /// Use a UART which has already been configured, e.g. by the bootloader
+    ///
+    /// DLAB is cleared in case it was left set.
+    pub fn assume_configured(uart: Uart8250<A>) -> Self {
+        uart.disable_divisor_latch_accessible();
+        Self {
+            uart,
+            _state: PhantomData,
+        }
+    }
+
+    /// Calls `f` with the divisor latch, see [`Uart8250::with_divisor_latch`]
+    pub fn with_divisor_latch<R, F: FnOnce(&Latch<'_, A>) -> R>(&self, f: F) -> R {
+        self.uart.with_divisor_latch(f)
+    }
+
+    /// Reads the line settings back from the UART, see [`Uart8250::line_config`]
+    pub fn line_config(&self, clock: usize) -> LineConfig {
+        self.uart.line_config(clock)
+    }
+
+    /// Reads a byte from the UART.
+    ///
+    /// Returns `None` when data is not ready (LSR\[0\] != 1)
+    pub fn read_byte(&self) -> Option<u8> {
+        self.uart.read_byte()
+    }
+
+    /// Writes a byte to the UART.
+    pub fn write_byte(&self, byte: u8) {
+        self.uart.write_byte(byte)
+    }
+
+    /// Get LSR bitflags
+    pub fn lsr(&self) -> LSR {
+        self.uart.lsr()
+    }
+
+    /// Read LSR once, see [`Uart8250::line_status`]
+    pub fn line_status(&self) -> LineStatus {
+        self.uart.line_status()
+    }
+
+    /// Get the error bits of every LSR read since the last call, see
+    /// [`Uart8250::take_line_errors`]
+    pub fn take_line_errors(&self) -> LSR {
+        self.uart.take_line_errors()
+    }
+
+    /// Read IIR once, see [`Uart8250::interrupt_id`]
+    pub fn interrupt_id(&self) -> InterruptId {
+        self.uart.interrupt_id()
+    }
+
+    /// Get MSR bitflags
+    pub fn msr(&self) -> MSR {
+        self.uart.msr()
+    }
+
+    /// Get IER bitflags
+    pub fn ier(&self) -> IER {
+        self.uart.ier()
+    }
+
+    /// Set IER via bitflags
+    pub fn set_ier(&self, flag: IER) {
+        self.uart.set_ier(flag)
+    }
+
+    /// Read IIR\[3:1\] to get interrupt type
+    pub fn read_interrupt_type(&self) -> Option<InterruptType> {
+        self.uart.read_interrupt_type()
+    }
+
+    /// Read IIR\[3:1\] to get interrupt type, see [`Uart8250::try_read_interrupt_type`]
+    pub fn try_read_interrupt_type(&self) -> Result<Option<InterruptType>, Error> {
+        self.uart.try_read_interrupt_type()
+    }
+
+    /// Services every pending interrupt cause, see [`Uart8250::dispatch_interrupts`]
+    pub fn dispatch_interrupts<H: InterruptHandler>(
+        &self,
+        handler: &mut H,
+    ) -> Result<usize, Error> {
+        self.uart.dispatch_interrupts(handler)
+    }
+
+    /// Read IIR\[7:6\] to get FIFO status
+    pub fn read_fifo_status(&self) -> ChipFifoInfo {
+        self.uart.read_fifo_status()
+    }
+
+    /// Write FCR to control FIFO buffers
+    pub fn write_fcr(&self, value: u8) {
+        self.uart.write_fcr(value)
+    }
+
+    /// Get the active FIFO configuration
+    pub fn fifo_config(&self) -> FifoConfig {
+        self.uart.fifo_config()
+    }
+
+    /// Write the FIFO configuration
+    pub fn set_fifo_config(&self, config: &FifoConfig) {
+        self.uart.set_fifo_config(config)
+    }
+
+    /// Read MCR
+    pub fn read_mcr(&self) -> u8 {
+        self.uart.read_mcr()
+    }
+
+    /// Write MCR
+    pub fn write_mcr(&self, value: u8) {
+        self.uart.write_mcr(value)
+    }
+
+    /// Reads the next received character together with its LSR bits, if there is one
+    pub fn read_event(&self) -> Option<RxEvent> {
+        self.uart.read_event()
+    }
+
+    /// Sends a break for `duration`, timed by `delay`
+    pub fn send_break<D: Delay>(&self, duration: Duration, delay: &mut D) {
+        self.uart.send_break(duration, delay)
+    }
+
+    /// Starts sending a break
+    pub fn start_break(&self) {
+        self.uart.start_break()
+    }
+
+    /// Stops sending a break
+    pub fn stop_break(&self) {
+        self.uart.stop_break()
+    }
+
+    /// Get MCR bitflags
+    pub fn mcr(&self) -> MCR {
+        self.uart.mcr()
+    }
+
+    /// Set MCR bitflags
+    pub fn set_mcr(&self, flag: MCR) {
+        self.uart.set_mcr(flag)
+    }
+
+    /// get parity of used data protocol
+    pub fn get_parity(&self) -> Parity {
+        self.uart.get_parity()
+    }
+
+    /// get parity of used data protocol, see [`Uart8250::try_get_parity`]
+    pub fn try_get_parity(&self) -> Result<Parity, Error> {
+        self.uart.try_get_parity()
+    }
+
+    /// set parity
+    pub fn set_parity(&self, parity: Parity) {
+        self.uart.set_parity(parity)
+    }
+
+    /// get stop bit of used data protocol
+    pub fn get_stop_bit(&self) -> u8 {
+        self.uart.get_stop_bit()
+    }
+
+    /// set stop bit, only 1 and 2 can be used as `stop_bit`
+    pub fn set_stop_bit(&self, stop_bit: u8) {
+        self.uart.set_stop_bit(stop_bit)
+    }
+
+    /// set stop bit, or return an error if `stop_bit` isn't 1 or 2
+    pub fn try_set_stop_bit(&self, stop_bit: u8) -> Result<(), Error> {
+        self.uart.try_set_stop_bit(stop_bit)
+    }
+
+    /// get word length of used data protocol
+    pub fn get_word_length(&self) -> u8 {
+        self.uart.get_word_length()
+    }
+
+    /// set word length, only 5..=8 can be used as `length`
+    pub fn set_word_length(&self, length: u8) {
+        self.uart.set_word_length(length)
+    }
+
+    /// set word length, or return an error if `length` isn't in 5..=8
+    pub fn try_set_word_length(&self, length: u8) -> Result<(), Error> {
+        self.uart.try_set_word_length(length)
+    }
+}
+
+/// ## fmt::Write
+#[cfg(feature = "fmt")]
+impl<A: RegisterAccess> fmt::Write for Uart<A, Configured> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.uart.write_str(s)
+    }
+}
@@ -1,8 +1,22 @@
 use bitflags::bitflags;
+use core::cell::Cell;
 #[cfg(feature = "fmt")]
 use core::fmt;
 
-use crate::registers::Registers;
+#[cfg(target_has_atomic = "8")]
+use crate::access::MmioDevice;
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+use crate::access::Port;
+use crate::access::{DefaultLayout, Mmio, RegisterAccess, RegisterLayout};
+use crate::registers::offset;
+#[cfg(any(feature = "embedded-hal-nb", feature = "embedded-io"))]
+use crate::LineError;
+use crate::{
+    BaudPlan, DivisorExtension, DivisorVariant, Error, FifoConfig, FifoMode, InterruptId,
+    LineConfig, LineStatus,
+};
+#[cfg(feature = "embedded-hal-nb")]
+use embedded_hal_nb::nb;
 
 bitflags! {
     /// Interrupt Enable Register (bitflags)
@@ -44,6 +58,31 @@ bitflags! {
     }
 }
 
+/// The LSR bits cleared by reading LSR
+pub(crate) const LINE_ERRORS: LSR =
+    LSR::from_bits_truncate(LSR::OE.bits() | LSR::PE.bits() | LSR::FE.bits() | LSR::BI.bits());
+
+/// LCR\[7\], which switches offsets 0 and 1 to the divisor latch
+pub(crate) const LCR_DLAB: u8 = 0b1000_0000;
+
+/// LCR\[6\], which holds the line in break
+pub(crate) const LCR_BREAK: u8 = 0b0100_0000;
+
+/// LCR for 8 data bits, no parity and 1 stop bit
+pub(crate) const LCR_8N1: u8 = 0b0000_0011;
+
+/// LCR\[1:0\], the word length minus 5
+pub(crate) const LCR_WORD_LENGTH: u8 = 0b0000_0011;
+
+/// LCR\[2\], set for 2 stop bits
+pub(crate) const LCR_STOP_BITS: u8 = 0b0000_0100;
+
+/// LCR\[5:3\], the parity
+pub(crate) const LCR_PARITY: u8 = 0b0011_1000;
+
+/// OX16C950 ACR\[6\], which makes ICR read the register selected by SPR
+const ACR_ICR_READ: u8 = 0b0100_0000;
+
 bitflags! {
     /// Modem Status Register (bitflags)
     pub struct MSR: u8 {
@@ -66,6 +105,44 @@ bitflags! {
     }
 }
 
+bitflags! {
+    /// Modem Control Register (bitflags)
+    pub struct MCR: u8 {
+        /// Data Terminal Ready
+        const DTR = 0b0000_0001;
+        /// Request To Send
+        const RTS = 0b0000_0010;
+        /// Auxiliary Output 1
+        const OUT1 = 0b0000_0100;
+        /// Auxiliary Output 2, gates the interrupt output on PC style UARTs
+        const OUT2 = 0b0000_1000;
+        /// Loopback Mode
+        const LOOPBACK = 0b0001_0000;
+        /// Autoflow Control Enabled (16750)
+        const AFE = 0b0010_0000;
+    }
+}
+
+bitflags! {
+    /// FIFO Control Register (bitflags)
+    pub struct FCR: u8 {
+        /// Enable FIFOs
+        const ENABLE = 0b0000_0001;
+        /// Clear Receive FIFO, self clearing
+        const CLEAR_RX = 0b0000_0010;
+        /// Clear Transmit FIFO, self clearing
+        const CLEAR_TX = 0b0000_0100;
+        /// DMA Mode Select
+        const DMA_MODE = 0b0000_1000;
+        /// Enable 64 Byte FIFO (16750), only written while DLAB is set
+        const FIFO_64BYTE = 0b0010_0000;
+        /// Receiver Trigger Level, low bit
+        const RX_TRIGGER_LSB = 0b0100_0000;
+        /// Receiver Trigger Level, high bit
+        const RX_TRIGGER_MSB = 0b1000_0000;
+    }
+}
+
 #[derive(Copy, Clone, Debug, Eq, PartialEq)]
 pub enum ChipFifoInfo {
     NoFifo,
@@ -93,43 +170,255 @@ pub enum Parity {
     Space,
 }
 
+impl Parity {
+    /// Get LCR\[5:3\] for the parity
+    pub(crate) const fn lcr(self) -> u8 {
+        match self {
+            Parity::No => 0b0000_0000,
+            Parity::Odd => 0b0000_1000,
+            Parity::Even => 0b0001_1000,
+            Parity::Mark => 0b0010_1000,
+            Parity::Space => 0b0011_1000,
+        }
+    }
+
+    /// Decode LCR\[5:3\], or get `None` if LCR\[5:4\] are set without LCR\[3\]
+    pub(crate) fn from_lcr(lcr: u8) -> Option<Self> {
+        match lcr & LCR_PARITY {
+            0b0000_0000 => Some(Parity::No),
+            0b0000_1000 => Some(Parity::Odd),
+            0b0001_1000 => Some(Parity::Even),
+            0b0010_1000 => Some(Parity::Mark),
+            0b0011_1000 => Some(Parity::Space),
+            _ => None,
+        }
+    }
+}
+
+/// # An 8250 UART
+///
+/// The register logic is shared by every way of reaching the registers, see [`RegisterAccess`].
+pub struct Uart8250<A: RegisterAccess> {
+    access: A,
+    divisor_variant: DivisorVariant,
+    /// Last value written to FCR, which can't be read back
+    fcr: Cell<u8>,
+    /// Last value written to IER, changed with [`modify_ier`](Self::modify_ier)
+    ier: Cell<u8>,
+    /// Last value written to the OX16C950 ACR, which can't be read back
+    acr: Cell<u8>,
+    /// LSR error bits read but not returned yet, e.g. by the transmit half for the receive half
+    #[cfg(any(
+        feature = "split",
+        feature = "embedded-io",
+        feature = "embedded-hal-nb"
+    ))]
+    rx_errors: Cell<u8>,
+    /// LSR error bits seen since the last [`take_line_errors`](Self::take_line_errors)
+    line_errors: Cell<u8>,
+}
+
 /// # MMIO version of an 8250 UART.
 ///
+/// Registers are bytes at consecutive addresses unless another [`RegisterLayout`] is given.
+///
 /// **Note** This is only tested on the NS16550 compatible UART used in QEMU 5.0 virt machine of RISC-V.
-pub struct MmioUart8250<'a> {
-    reg: &'a mut Registers,
-}
+pub type MmioUart8250<'a, L = DefaultLayout> = Uart8250<Mmio<'a, L>>;
+
+/// # Port I/O version of an 8250 UART, such as the legacy PC COM ports.
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+pub type PioUart8250 = Uart8250<Port>;
 
-impl<'a> MmioUart8250<'a> {
+impl<'a, L: RegisterLayout + Default> MmioUart8250<'a, L> {
     /// Creates a new UART.
-    pub fn new(base_address: usize) -> Self {
+    ///
+    /// # Safety
+    ///
+    /// See [`Mmio::new`]. [`MmioUart8250::take`] is a safe way to get the UART.
+    pub unsafe fn new(base_address: usize) -> Self {
+        Self::with_layout(base_address, L::default())
+    }
+}
+
+impl<'a, L: RegisterLayout> MmioUart8250<'a, L> {
+    /// Creates a new UART whose registers are placed according to `layout`.
+    ///
+    /// ```no_run
+    /// use uart8250::access::{MmioLayout, RegisterWidth};
+    /// use uart8250::MmioUart8250;
+    ///
+    /// // DesignWare APB UART: 32-bit registers, 4 bytes apart
+    /// let uart = unsafe {
+    ///     MmioUart8250::with_layout(
+    ///         0x1000_0000,
+    ///         MmioLayout {
+    ///             reg_shift: 2,
+    ///             reg_io_width: RegisterWidth::U32,
+    ///             big_endian: false,
+    ///         },
+    ///     )
+    /// };
+    /// uart.init(24_000_000, 115200);
+    /// ```
+    ///
+    /// # Safety
+    ///
+    /// See [`Mmio::new`].
+    pub const unsafe fn with_layout(base_address: usize, layout: L) -> Self {
+        Self::from_access(Mmio::new(base_address, layout))
+    }
+
+    /// Sets a new base address for the UART, keeping the register layout.
+    ///
+    /// # Safety
+    ///
+    /// See [`Mmio::new`].
+    pub unsafe fn set_base_address(&mut self, base_address: usize)
+    where
+        L: Clone,
+    {
+        self.access = Mmio::new(base_address, self.access.layout().clone());
+    }
+}
+
+#[cfg(target_has_atomic = "8")]
+impl<L: RegisterLayout> MmioUart8250<'static, L> {
+    /// Takes the UART of `device`, which only succeeds the first time.
+    ///
+    /// ```no_run
+    /// use uart8250::access::{DefaultLayout, MmioDevice};
+    /// use uart8250::MmioUart8250;
+    ///
+    /// static UART0: MmioDevice = unsafe { MmioDevice::new(0x1000_0000) };
+    ///
+    /// let uart = MmioUart8250::take(&UART0, DefaultLayout).unwrap();
+    /// assert!(MmioUart8250::take(&UART0, DefaultLayout).is_none());
+    /// ```
+    pub fn take(device: &MmioDevice, layout: L) -> Option<Self> {
+        let base_address = device.claim()?;
+        // SAFETY: `MmioDevice::new` promised the address is valid, and it is only handed out once.
+        Some(unsafe { Self::with_layout(base_address, layout) })
+    }
+}
+
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+impl PioUart8250 {
+    /// Creates a new UART at the given I/O port, e.g. `0x3F8` for COM1.
+    ///
+    /// # Safety
+    ///
+    /// See [`Port::new`].
+    pub const unsafe fn new(base: u16) -> Self {
+        Self {
+            access: Port::new(base),
+            divisor_variant: DivisorVariant::Standard,
+            fcr: Cell::new(0),
+            ier: Cell::new(0),
+            acr: Cell::new(0),
+            #[cfg(any(
+                feature = "split",
+                feature = "embedded-io",
+                feature = "embedded-hal-nb"
+            ))]
+            rx_errors: Cell::new(0),
+            line_errors: Cell::new(0),
+        }
+    }
+}
+
+impl<A: RegisterAccess> Uart8250<A> {
+    /// Creates a new UART using the given register access.
+    pub const fn from_access(access: A) -> Self {
         Self {
-            reg: Registers::from_base_address(base_address),
+            access,
+            divisor_variant: DivisorVariant::Standard,
+            fcr: Cell::new(0),
+            ier: Cell::new(0),
+            acr: Cell::new(0),
+            #[cfg(any(
+                feature = "split",
+                feature = "embedded-io",
+                feature = "embedded-hal-nb"
+            ))]
+            rx_errors: Cell::new(0),
+            line_errors: Cell::new(0),
         }
     }
 
+    /// Uses the extended baud rate generator of `variant` when setting the divisor.
+    ///
+    /// ```no_run
+    /// use uart8250::{DivisorVariant, MmioUart8250};
+    ///
+    /// let uart: MmioUart8250 = unsafe { MmioUart8250::new(0x1000_0000) };
+    /// let uart = uart.with_divisor_variant(DivisorVariant::Ox16C950);
+    /// // 62.5 MHz / (10 samples per bit * 2)
+    /// uart.set_divisor(62_500_000, 3_125_000);
+    /// ```
+    pub const fn with_divisor_variant(mut self, variant: DivisorVariant) -> Self {
+        self.divisor_variant = variant;
+        self
+    }
+
+    /// Get the baud rate generator used when setting the divisor
+    pub fn divisor_variant(&self) -> DivisorVariant {
+        self.divisor_variant
+    }
+
     /// Initialises the UART with common settings and interrupts enabled.
     ///
     /// More customised initialisation can be done using other methods below.
     pub fn init(&self, clock: usize, baud_rate: usize) {
-        // Enable DLAB and Set divisor
-        self.set_divisor(clock, baud_rate);
-
-        // Disable DLAB and set word length 8 bits, no parity, 1 stop bit
-        self.write_lcr(3);
-        // Enable FIFO
-        self.write_fcr(1);
+        // 8 bits, no parity, 1 stop bit, FIFO and received_data_available_interrupt enabled
+        self.apply_line_config(clock, &LineConfig::new(baud_rate));
         // No modem control
         self.write_mcr(0);
-        // Enable received_data_available_interrupt
-        self.enable_received_data_available_interrupt();
-        // Enable transmitter_holding_register_empty_interrupt
-        // self.enable_transmitter_holding_register_empty_interrupt();
     }
 
-    /// Sets a new base address for the UART.
-    pub fn set_base_address(&mut self, base_address: usize) {
-        self.reg = Registers::from_base_address(base_address);
+    /// Applies all of `config` in one go.
+    ///
+    /// Interrupts are disabled while the divisor, LCR and FCR are written, and `config.interrupts`
+    /// are enabled last. The nearest divisor is used, and must be within
+    /// [`BaudPlan::DEFAULT_TOLERANCE_PPM`].
+    ///
+    /// Panics before touching any register if `config` is invalid, see
+    /// [`try_apply_line_config`](Self::try_apply_line_config).
+    pub fn apply_line_config(&self, clock: usize, config: &LineConfig) {
+        if let Err(error) = self.try_apply_line_config(clock, config) {
+            panic!("{}", error)
+        }
+    }
+
+    /// Applies all of `config` in one go, or returns an error without touching any register.
+    pub fn try_apply_line_config(&self, clock: usize, config: &LineConfig) -> Result<(), Error> {
+        let lcr = config.lcr()?;
+        let plan = BaudPlan::for_variant(self.divisor_variant, clock, config.baud_rate)?
+            .within(BaudPlan::DEFAULT_TOLERANCE_PPM)?;
+
+        self.set_ier(IER::empty());
+        self.write_lcr(lcr);
+        self.write_baud_plan(&plan);
+        self.set_fcr(match config.fifo {
+            FifoMode::Disabled => FCR::empty(),
+            FifoMode::Enabled => FCR::ENABLE,
+        });
+        self.set_ier(config.interrupts);
+        Ok(())
+    }
+
+    /// Reads the line settings back from the UART.
+    ///
+    /// The baud rate is recovered from DLL/DLH and `clock`, and is 0 if the divisor is 0.
+    /// The FIFO mode is taken from the copy of FCR, see [`fcr`](Self::fcr).
+    pub fn line_config(&self, clock: usize) -> LineConfig {
+        let lcr = self.read_lcr();
+        let baud_rate = self.read_baud_plan(clock).actual_baud_rate;
+        let fifo = if self.fcr().contains(FCR::ENABLE) {
+            FifoMode::Enabled
+        } else {
+            FifoMode::Disabled
+        };
+        LineConfig::from_lcr(lcr, baud_rate, fifo, self.ier())
     }
 
     /// Reads a byte from the UART.
@@ -163,7 +452,7 @@ impl<'a> MmioUart8250<'a> {
     /// > If the receive buffer is occupied or the FIFO is full, the incoming data is discarded and the Receiver Line Status interrupt is written to the IIR register. The Overrun Error bit is also set in the Line Status Register.
     #[inline]
     pub fn write_thr(&self, value: u8) {
-        unsafe { self.reg.thr_rbr_dll.write(value) }
+        self.access.write(offset::THR, value)
     }
 
     /// read RBR (offset + 0)
@@ -171,7 +460,7 @@ impl<'a> MmioUart8250<'a> {
     /// Read Receiver Buffer to get data
     #[inline]
     pub fn read_rbr(&self) -> u8 {
-        self.reg.thr_rbr_dll.read()
+        self.access.read(offset::RBR)
     }
 
     /// read DLL (offset + 0)
@@ -205,7 +494,7 @@ impl<'a> MmioUart8250<'a> {
     /// | 115200    | 1                    | $00                     | $01                    |
     #[inline]
     pub fn read_dll(&self) -> u8 {
-        self.reg.thr_rbr_dll.read()
+        self.access.read(offset::DLL)
     }
 
     /// write DLL (offset + 0)
@@ -213,7 +502,7 @@ impl<'a> MmioUart8250<'a> {
     /// set divisor latch low byte in the register
     #[inline]
     pub fn write_dll(&self, value: u8) {
-        unsafe { self.reg.thr_rbr_dll.write(value) }
+        self.access.write(offset::DLL, value)
     }
 
     /// read DLH (offset + 1)
@@ -221,7 +510,7 @@ impl<'a> MmioUart8250<'a> {
     /// get divisor latch high byte in the register
     #[inline]
     pub fn read_dlh(&self) -> u8 {
-        self.reg.ier_dlh.read()
+        self.access.read(offset::DLH)
     }
 
     /// write DLH (offset + 1)
@@ -229,17 +518,212 @@ impl<'a> MmioUart8250<'a> {
     /// set divisor latch high byte in the register
     #[inline]
     pub fn write_dlh(&self, value: u8) {
-        unsafe { self.reg.ier_dlh.write(value) }
+        self.access.write(offset::DLH, value)
     }
 
-    /// Set divisor latch according to clock and baud_rate, then set DLAB to false
+    /// Set divisor latch to the nearest divisor for clock and baud_rate, restoring LCR afterwards
+    ///
+    /// The variant specific registers are set too, see [`with_divisor_variant`](Self::with_divisor_variant).
+    /// Panics if there is no such divisor, see [`try_set_divisor`](Self::try_set_divisor).
     #[inline]
     pub fn set_divisor(&self, clock: usize, baud_rate: usize) {
-        self.enable_divisor_latch_accessible();
-        let divisor = clock / (16 * baud_rate);
-        self.write_dll(divisor as u8);
-        self.write_dlh((divisor >> 8) as u8);
-        self.disable_divisor_latch_accessible();
+        let plan = BaudPlan::for_variant(self.divisor_variant, clock, baud_rate)
+            .expect("Unsupported baud rate");
+        self.write_baud_plan(&plan);
+    }
+
+    /// Set divisor latch to the nearest divisor for clock and baud_rate, restoring LCR afterwards
+    ///
+    /// Returns an error without touching any register if the baud rate would be off by more than
+    /// `tolerance_ppm`, see [`BaudPlan::within`].
+    pub fn try_set_divisor(
+        &self,
+        clock: usize,
+        baud_rate: usize,
+        tolerance_ppm: u32,
+    ) -> Result<BaudPlan, Error> {
+        let plan =
+            BaudPlan::for_variant(self.divisor_variant, clock, baud_rate)?.within(tolerance_ppm)?;
+        self.write_baud_plan(&plan);
+        Ok(plan)
+    }
+
+    /// Reads the divisor latch and the variant specific registers back, with the baud rate they give
+    ///
+    /// `error_ppm` is 0, as the intended baud rate isn't known.
+    pub fn read_baud_plan(&self, clock: usize) -> BaudPlan {
+        let divisor = self.with_divisor_latch(|latch| latch.divisor());
+        let extension = self.read_divisor_extension();
+        BaudPlan {
+            divisor,
+            extension,
+            actual_baud_rate: BaudPlan::baud_rate_with(clock, divisor, extension),
+            error_ppm: 0,
+        }
+    }
+
+    /// Set divisor latch and the variant specific registers according to `plan`
+    ///
+    /// The divisor latch is written with [`with_divisor_latch`](Self::with_divisor_latch).
+    /// Panics if the extension is out of range, see [`try_write_baud_plan`](Self::try_write_baud_plan).
+    pub fn write_baud_plan(&self, plan: &BaudPlan) {
+        self.try_write_baud_plan(plan).expect("Invalid baud plan")
+    }
+
+    /// Set divisor latch and the variant specific registers according to `plan`
+    ///
+    /// Returns an error without touching any register if the extension is out of range, see
+    /// [`BaudPlan::validate`].
+    pub fn try_write_baud_plan(&self, plan: &BaudPlan) -> Result<(), Error> {
+        let plan = plan.validate()?;
+        self.with_divisor_latch(|latch| latch.write_divisor(plan.divisor));
+        self.write_divisor_extension(plan.extension);
+        Ok(())
+    }
+
+    fn write_divisor_extension(&self, extension: DivisorExtension) {
+        match extension {
+            DivisorExtension::None => {}
+            DivisorExtension::Fraction { dlf, .. } => self.access.write(offset::DW_DLF, dlf),
+            DivisorExtension::FractionalDivider { mulval, divaddval } => self
+                .access
+                .write(offset::LPC_FDR, (mulval << 4) | (divaddval & 0b1111)),
+            // 16 samples is written as 0
+            DivisorExtension::SamplesPerBit(samples) => {
+                self.write_icr(offset::icr::TCR, samples & 0b1111)
+            }
+            DivisorExtension::SampleCount(samples) => {
+                self.access.write(offset::MTK_HIGHSPEED, 3);
+                self.access
+                    .write(offset::MTK_SAMPLE_COUNT, (samples - 1) as u8);
+                self.access
+                    .write(offset::MTK_SAMPLE_POINT, ((samples >> 1) - 1) as u8);
+            }
+        }
+    }
+
+    fn read_divisor_extension(&self) -> DivisorExtension {
+        match self.divisor_variant {
+            DivisorVariant::Standard => DivisorExtension::None,
+            DivisorVariant::DesignWare { dlf_bits } => {
+                let bits = dlf_bits.min(8);
+                DivisorExtension::Fraction {
+                    dlf: (self.access.read(offset::DW_DLF) as u16 & ((1 << bits) - 1)) as u8,
+                    bits,
+                }
+            }
+            DivisorVariant::NxpLpc => {
+                let fdr = self.access.read(offset::LPC_FDR);
+                match (fdr >> 4, fdr & 0b1111) {
+                    // MULVAL of 0 is reserved, and DIVADDVAL of 0 disables the fractional divider
+                    (0, _) | (_, 0) => DivisorExtension::None,
+                    (mulval, divaddval) => {
+                        DivisorExtension::FractionalDivider { mulval, divaddval }
+                    }
+                }
+            }
+            DivisorVariant::Ox16C950 => match self.read_icr(offset::icr::TCR) & 0b1111 {
+                0..=3 => DivisorExtension::None,
+                samples => DivisorExtension::SamplesPerBit(samples),
+            },
+            DivisorVariant::MediaTek => match self.access.read(offset::MTK_HIGHSPEED) & 0b11 {
+                0 => DivisorExtension::None,
+                1 => DivisorExtension::SampleCount(8),
+                2 => DivisorExtension::SampleCount(4),
+                // A sample count of 1 can't be set, so it is read as the lowest one which can
+                _ => DivisorExtension::SampleCount(
+                    (self.access.read(offset::MTK_SAMPLE_COUNT) as u16 + 1).max(2),
+                ),
+            },
+        }
+    }
+
+    /// Get the OX16C950 ACR
+    ///
+    /// ACR is write only, so this is a copy kept by the driver. It is 0, the reset value, until
+    /// ACR is first written through this `Uart8250`.
+    pub fn acr(&self) -> u8 {
+        self.acr.get()
+    }
+
+    /// Write the OX16C950 ACR through ICR, keeping a copy for [`acr`](Self::acr)
+    ///
+    /// SPR is overwritten to select ACR.
+    pub fn set_acr(&self, value: u8) {
+        self.acr.set(value);
+        self.write_icr(offset::icr::ACR, value);
+    }
+
+    /// Write a OX16C950 register through ICR
+    pub(crate) fn write_icr(&self, index: u8, value: u8) {
+        self.write_sr(index);
+        self.access.write(offset::ICR, value);
+    }
+
+    /// Read a OX16C950 register through ICR
+    ///
+    /// Reading needs ACR\[6\], which is set for the read and then put back as in [`acr`](Self::acr).
+    pub(crate) fn read_icr(&self, index: u8) -> u8 {
+        self.write_icr(offset::icr::ACR, self.acr.get() | ACR_ICR_READ);
+        self.write_sr(index);
+        let value = self.access.read(offset::ICR);
+        self.write_icr(offset::icr::ACR, self.acr.get());
+        value
+    }
+
+    /// Read EFR, which is only there while LCR is 0xbf
+    pub(crate) fn read_efr(&self) -> u8 {
+        self.access.read(offset::EFR)
+    }
+
+    /// Write EFR, which is only there while LCR is 0xbf
+    pub(crate) fn write_efr(&self, value: u8) {
+        self.access.write(offset::EFR, value)
+    }
+
+    /// Keep the error bits of `lsr` for the next read
+    #[cfg(any(
+        feature = "split",
+        feature = "embedded-io",
+        feature = "embedded-hal-nb"
+    ))]
+    pub(crate) fn keep_rx_errors(&self, lsr: LSR) {
+        self.locked(|| {
+            self.rx_errors
+                .set(self.rx_errors.get() | (lsr & LINE_ERRORS).bits())
+        });
+    }
+
+    /// Read LSR for the transmitter, keeping its error bits for the next read
+    #[cfg(any(
+        feature = "split",
+        feature = "embedded-io",
+        feature = "embedded-hal-nb"
+    ))]
+    pub(crate) fn tx_lsr(&self) -> LSR {
+        self.locked(|| {
+            let lsr = self.raw_lsr();
+            self.keep_rx_errors(lsr);
+            lsr
+        })
+    }
+
+    /// Take the error bits kept by [`keep_rx_errors`](Self::keep_rx_errors)
+    #[cfg(any(
+        feature = "split",
+        feature = "embedded-io",
+        feature = "embedded-hal-nb"
+    ))]
+    pub(crate) fn take_rx_errors(&self) -> LSR {
+        LSR::from_bits_truncate(self.locked(|| self.rx_errors.replace(0)))
+    }
+
+    /// Run `f` in a critical section with feature `critical-section`, or just run it
+    pub(crate) fn locked<R, F: FnOnce() -> R>(&self, f: F) -> R {
+        #[cfg(feature = "critical-section")]
+        return critical_section::with(|_| f());
+        #[cfg(not(feature = "critical-section"))]
+        f()
     }
 
     /// Read IER (offset + 1)
@@ -264,27 +748,57 @@ impl<'a> MmioUart8250<'a> {
     /// > | 0   | Enable Received Data Available Interrupt            |
     #[inline]
     pub fn read_ier(&self) -> u8 {
-        self.reg.ier_dlh.read()
+        self.access.read(offset::IER)
     }
 
     /// Write IER (offset + 1)
     ///
-    /// Write Interrupt Enable Register to turn on/off interrupts
+    /// Write Interrupt Enable Register to turn on/off interrupts, keeping a copy of it
     #[inline]
     pub fn write_ier(&self, value: u8) {
-        unsafe { self.reg.ier_dlh.write(value) }
+        self.update_ier(|_| value);
     }
 
     /// Get IER bitflags
+    ///
+    /// This is the copy of the last value written, which isn't affected by what the chip
+    /// returns when IER is read, e.g. the sleep and low power mode bits of a 16750.
     #[inline]
     pub fn ier(&self) -> IER {
-        IER::from_bits_truncate(self.read_ier())
+        IER::from_bits_truncate(self.ier.get())
     }
 
     /// Set IER via bitflags
     #[inline]
     pub fn set_ier(&self, flag: IER) {
-        self.write_ier(flag.bits())
+        self.update_ier(|_| flag.bits());
+    }
+
+    /// Change IER based on the copy of the last value written, returning the new value
+    ///
+    /// With feature `critical-section` the change is made in a critical section, so thread code
+    /// and an interrupt handler can both change IER without undoing each other's change. The
+    /// lock is the critical section implementation of the application, e.g. a spinlock on
+    /// multicore systems. Without the feature, callers have to hold their own lock, or mask the
+    /// UART interrupt, while calling this.
+    ///
+    /// Every `enable_`, `disable_` and `toggle_` interrupt method uses this.
+    pub fn modify_ier<F: FnOnce(IER) -> IER>(&self, f: F) -> IER {
+        IER::from_bits_truncate(self.update_ier(|ier| f(IER::from_bits_truncate(ier)).bits()))
+    }
+
+    /// Copies IER into the copy kept by the driver, e.g. when the UART was set up before
+    pub fn reload_ier(&self) {
+        self.ier.set(self.read_ier());
+    }
+
+    fn update_ier<F: FnOnce(u8) -> u8>(&self, f: F) -> u8 {
+        self.locked(|| {
+            let ier = f(self.ier.get());
+            self.ier.set(ier);
+            self.access.write(offset::IER, ier);
+            ier
+        })
     }
 
     /// get whether low power mode (16750) is enabled (IER\[5\])
@@ -294,17 +808,17 @@ impl<'a> MmioUart8250<'a> {
 
     /// toggle low power mode (16750) (IER\[5\])
     pub fn toggle_low_power_mode(&self) {
-        self.set_ier(self.ier() ^ IER::LPM)
+        self.modify_ier(|ier| ier ^ IER::LPM);
     }
 
     /// enable low power mode (16750) (IER\[5\])
     pub fn enable_low_power_mode(&self) {
-        self.set_ier(self.ier() | IER::LPM)
+        self.modify_ier(|ier| ier | IER::LPM);
     }
 
     /// disable low power mode (16750) (IER\[5\])
     pub fn disable_low_power_mode(&self) {
-        self.set_ier(self.ier() & !IER::LPM)
+        self.modify_ier(|ier| ier & !IER::LPM);
     }
 
     /// get whether sleep mode (16750) is enabled (IER\[4\])
@@ -314,17 +828,17 @@ impl<'a> MmioUart8250<'a> {
 
     /// toggle sleep mode (16750) (IER\[4\])
     pub fn toggle_sleep_mode(&self) {
-        self.set_ier(self.ier() ^ IER::SM)
+        self.modify_ier(|ier| ier ^ IER::SM);
     }
 
     /// enable sleep mode (16750) (IER\[4\])
     pub fn enable_sleep_mode(&self) {
-        self.set_ier(self.ier() | IER::SM)
+        self.modify_ier(|ier| ier | IER::SM);
     }
 
     /// disable sleep mode (16750) (IER\[4\])
     pub fn disable_sleep_mode(&self) {
-        self.set_ier(self.ier() & !IER::SM)
+        self.modify_ier(|ier| ier & !IER::SM);
     }
 
     /// get whether modem status interrupt is enabled (IER\[3\])
@@ -334,17 +848,17 @@ impl<'a> MmioUart8250<'a> {
 
     /// toggle modem status interrupt (IER\[3\])
     pub fn toggle_modem_status_interrupt(&self) {
-        self.set_ier(self.ier() ^ IER::MSI)
+        self.modify_ier(|ier| ier ^ IER::MSI);
     }
 
     /// enable modem status interrupt (IER\[3\])
     pub fn enable_modem_status_interrupt(&self) {
-        self.set_ier(self.ier() | IER::MSI)
+        self.modify_ier(|ier| ier | IER::MSI);
     }
 
     /// disable modem status interrupt (IER\[3\])
     pub fn disable_modem_status_interrupt(&self) {
-        self.set_ier(self.ier() & !IER::MSI)
+        self.modify_ier(|ier| ier & !IER::MSI);
     }
 
     /// get whether receiver line status interrupt is enabled (IER\[2\])
@@ -354,17 +868,17 @@ impl<'a> MmioUart8250<'a> {
 
     /// toggle receiver line status interrupt (IER\[2\])
     pub fn toggle_receiver_line_status_interrupt(&self) {
-        self.set_ier(self.ier() ^ IER::RLSI)
+        self.modify_ier(|ier| ier ^ IER::RLSI);
     }
 
     /// enable receiver line status interrupt (IER\[2\])
     pub fn enable_receiver_line_status_interrupt(&self) {
-        self.set_ier(self.ier() | IER::RLSI)
+        self.modify_ier(|ier| ier | IER::RLSI);
     }
 
     /// disable receiver line status interrupt (IER\[2\])
     pub fn disable_receiver_line_status_interrupt(&self) {
-        self.set_ier(self.ier() & !IER::RLSI)
+        self.modify_ier(|ier| ier & !IER::RLSI);
     }
 
     /// get whether transmitter holding register empty interrupt is enabled (IER\[1\])
@@ -374,17 +888,17 @@ impl<'a> MmioUart8250<'a> {
 
     /// toggle transmitter holding register empty interrupt (IER\[1\])
     pub fn toggle_transmitter_holding_register_empty_interrupt(&self) {
-        self.set_ier(self.ier() ^ IER::THREI)
+        self.modify_ier(|ier| ier ^ IER::THREI);
     }
 
     /// enable transmitter holding register empty interrupt (IER\[1\])
     pub fn enable_transmitter_holding_register_empty_interrupt(&self) {
-        self.set_ier(self.ier() | IER::THREI)
+        self.modify_ier(|ier| ier | IER::THREI);
     }
 
     /// disable transmitter holding register empty interrupt (IER\[1\])
     pub fn disable_transmitter_holding_register_empty_interrupt(&self) {
-        self.set_ier(self.ier() & !IER::THREI)
+        self.modify_ier(|ier| ier & !IER::THREI);
     }
 
     /// get whether received data available is enabled (IER\[0\])
@@ -394,17 +908,17 @@ impl<'a> MmioUart8250<'a> {
 
     /// toggle received data available (IER\[0\])
     pub fn toggle_received_data_available_interrupt(&self) {
-        self.set_ier(self.ier() ^ IER::RDAI)
+        self.modify_ier(|ier| ier ^ IER::RDAI);
     }
 
     /// enable received data available (IER\[0\])
     pub fn enable_received_data_available_interrupt(&self) {
-        self.set_ier(self.ier() | IER::RDAI)
+        self.modify_ier(|ier| ier | IER::RDAI);
     }
 
     /// disable received data available (IER\[0\])
     pub fn disable_received_data_available_interrupt(&self) {
-        self.set_ier(self.ier() & !IER::RDAI)
+        self.modify_ier(|ier| ier & !IER::RDAI);
     }
 
     /// Read IIR (offset + 2)
@@ -441,40 +955,47 @@ impl<'a> MmioUart8250<'a> {
     /// > | 0          | Interrupt Pending Flag            |       |                                   |                                              |                                                                                           |
     #[inline]
     pub fn read_iir(&self) -> u8 {
-        self.reg.iir_fcr.read()
+        self.access.read(offset::IIR)
+    }
+
+    /// Read IIR once, to check several of its fields
+    ///
+    /// Reading IIR clears a THRE interrupt it reports, see [`InterruptId::interrupt_type`].
+    pub fn interrupt_id(&self) -> InterruptId {
+        InterruptId::from(self.read_iir())
     }
 
     /// Read IIR\[7:6\] to get FIFO status
     pub fn read_fifo_status(&self) -> ChipFifoInfo {
-        match self.reg.iir_fcr.read() & 0b1100_0000 {
-            0 => ChipFifoInfo::NoFifo,
-            0b0100_0000 => ChipFifoInfo::Reserved,
-            0b1000_0000 => ChipFifoInfo::EnabledNoFunction,
-            0b1100_0000 => ChipFifoInfo::Enabled,
-            _ => panic!("Can't reached"),
-        }
+        self.interrupt_id().fifo_status()
+    }
+
+    /// Read IIR\[7:6\] to get whether FIFO is enabled
+    pub fn is_fifo_enabled(&self) -> bool {
+        self.read_fifo_status() != ChipFifoInfo::NoFifo
     }
 
     /// get whether 64 Byte fifo (16750 only) is enabled (IIR\[5\])
     pub fn is_64byte_fifo_enabled(&self) -> bool {
-        self.reg.iir_fcr.read() & 0b0010_0000 != 0
+        self.interrupt_id().is_64byte_fifo_enabled()
     }
 
     /// Read IIR\[3:1\] to get interrupt type
     pub fn read_interrupt_type(&self) -> Option<InterruptType> {
-        let irq = self.reg.iir_fcr.read() & 0b0000_1111;
-        if irq & 1 != 0 {
-            None
-        } else {
-            match irq {
-                0b0000 => Some(InterruptType::ModemStatus),
-                0b0010 => Some(InterruptType::TransmitterHoldingRegisterEmpty),
-                0b0100 => Some(InterruptType::ReceivedDataAvailable),
-                0b0110 => Some(InterruptType::ReceiverLineStatus),
-                0b1100 => Some(InterruptType::Timeout),
-                0b1000 | 0b1010 | 0b1110 => Some(InterruptType::Reserved),
-                _ => panic!("Can't reached"),
-            }
+        self.interrupt_id().interrupt_type()
+    }
+
+    /// Read IIR\[3:1\] to get interrupt type
+    ///
+    /// Returns an error instead of [`InterruptType::Reserved`].
+    pub fn try_read_interrupt_type(&self) -> Result<Option<InterruptType>, Error> {
+        let iir = self.interrupt_id();
+        match iir.interrupt_type() {
+            Some(InterruptType::Reserved) => Err(Error::UnexpectedRegisterValue {
+                offset: offset::IIR,
+                value: iir.bits(),
+            }),
+            interrupt_type => Ok(interrupt_type),
         }
     }
 
@@ -482,9 +1003,22 @@ impl<'a> MmioUart8250<'a> {
     ///
     /// # Safety
     ///
-    /// read iir will reset THREI, so use read_interrupt_type may be better
+    /// read iir will reset THREI, so use [`interrupt_id`](Self::interrupt_id) to get the
+    /// interrupt type with the same read
     pub unsafe fn is_interrupt_pending(&self) -> bool {
-        self.reg.iir_fcr.read() & 1 == 0
+        self.access.read(offset::IIR) & 1 == 0
+    }
+
+    /// Read FCR (offset + 2)
+    ///
+    /// # Safety
+    ///
+    /// FCR is write only on most UARTs, and reading offset + 2 gives IIR instead.
+    /// Some UARTs, such as Xilinx's AXI UART 16550, return FCR while DLAB == 1.
+    /// Please make sure the UART supports this and DLAB is 1 when using this method.
+    #[inline]
+    pub unsafe fn read_fcr(&self) -> u8 {
+        self.access.read(offset::FCR)
     }
 
     /// Write FCR (offset + 2) to control FIFO buffers
@@ -508,9 +1042,56 @@ impl<'a> MmioUart8250<'a> {
     /// > | 2     | Clear Transmit FIFO         |       |                                   |                         |
     /// > | 1     | Clear Receive FIFO          |       |                                   |                         |
     /// > | 0     | Enable FIFOs                |       |                                   |                         |
+    ///
+    /// With feature `critical-section` FCR and the driver's copy of it are written in a critical
+    /// section, like IER, see [`modify_ier`](Self::modify_ier).
     #[inline]
     pub fn write_fcr(&self, value: u8) {
-        unsafe { self.reg.iir_fcr.write(value) }
+        self.locked(|| {
+            self.fcr
+                .set(value & !(FCR::CLEAR_RX | FCR::CLEAR_TX).bits());
+            self.access.write(offset::FCR, value)
+        })
+    }
+
+    /// Get the FIFO control last written, without the self clearing bits
+    ///
+    /// FCR is write only, so this is a copy kept by the driver. It is 0, the reset value, until
+    /// FCR is first written through this `Uart8250`.
+    pub fn fcr(&self) -> FCR {
+        FCR::from_bits_truncate(self.fcr.get())
+    }
+
+    /// Read FCR back into the driver's copy, and get it
+    ///
+    /// # Safety
+    ///
+    /// The UART must return FCR while DLAB is set, see [`read_fcr`](Self::read_fcr). DLAB is set
+    /// with [`with_divisor_latch`](Self::with_divisor_latch), which restores LCR afterwards.
+    pub unsafe fn reload_fcr(&self) -> FCR {
+        self.with_divisor_latch(|latch| {
+            let fcr = unsafe { latch.read_fcr() };
+            self.fcr.set(fcr & !(FCR::CLEAR_RX | FCR::CLEAR_TX).bits());
+        });
+        self.fcr()
+    }
+
+    /// Write FCR, like [`write_fcr`](Self::write_fcr)
+    pub fn set_fcr(&self, flag: FCR) {
+        self.write_fcr(flag.bits())
+    }
+
+    /// Get the active FIFO configuration, see [`fcr`](Self::fcr)
+    pub fn fifo_config(&self) -> FifoConfig {
+        FifoConfig::from_fcr(self.fcr())
+    }
+
+    /// Write `config` to FCR.
+    ///
+    /// DLAB is set meanwhile with [`with_divisor_latch`](Self::with_divisor_latch) so that the
+    /// 16750 takes [`FCR::FIFO_64BYTE`]. Enabling or disabling the FIFOs clears them.
+    pub fn set_fifo_config(&self, config: &FifoConfig) {
+        self.with_divisor_latch(|latch| latch.set_fcr(config.fcr()));
     }
 
     /// Read LCR (offset + 3)
@@ -543,7 +1124,7 @@ impl<'a> MmioUart8250<'a> {
     /// > |          | 1                        | 1                            | 8 Bits      |               |
     #[inline]
     pub fn read_lcr(&self) -> u8 {
-        self.reg.lcr.read()
+        self.access.read(offset::LCR)
     }
 
     /// Write LCR (offset + 3)
@@ -551,79 +1132,91 @@ impl<'a> MmioUart8250<'a> {
     /// Write Line Control Register to set DLAB and the serial data protocol
     #[inline]
     pub fn write_lcr(&self, value: u8) {
-        unsafe { self.reg.lcr.write(value) }
+        self.access.write(offset::LCR, value)
     }
 
     /// get whether DLAB is enabled
     pub fn is_divisor_latch_accessible(&self) -> bool {
-        self.reg.lcr.read() & 0b1000_0000 != 0
+        self.access.read(offset::LCR) & LCR_DLAB != 0
     }
 
     /// toggle DLAB
     pub fn toggle_divisor_latch_accessible(&self) {
-        unsafe { self.reg.lcr.modify(|v| v ^ 0b1000_0000) }
+        self.access.modify(offset::LCR, |v| v ^ LCR_DLAB)
     }
 
     /// enable DLAB
     pub fn enable_divisor_latch_accessible(&self) {
-        unsafe { self.reg.lcr.modify(|v| v | 0b1000_0000) }
+        self.access.modify(offset::LCR, |v| v | LCR_DLAB)
     }
 
     /// disable DLAB
     pub fn disable_divisor_latch_accessible(&self) {
-        unsafe { self.reg.lcr.modify(|v| v & !0b1000_0000) }
+        self.access.modify(offset::LCR, |v| v & !LCR_DLAB)
     }
 
     /// get parity of used data protocol
     pub fn get_parity(&self) -> Parity {
-        match self.reg.lcr.read() & 0b0011_1000 {
-            0b0000_0000 => Parity::No,
-            0b0000_1000 => Parity::Odd,
-            0b0001_1000 => Parity::Even,
-            0b0010_1000 => Parity::Mark,
-            0b0011_1000 => Parity::Space,
-            _ => panic!("Invalid Parity! Please check your uart"),
-        }
+        self.try_get_parity()
+            .expect("Invalid Parity! Please check your uart")
+    }
+
+    /// get parity of used data protocol, or an error if LCR\[5:4\] are set without LCR\[3\]
+    pub fn try_get_parity(&self) -> Result<Parity, Error> {
+        let lcr = self.access.read(offset::LCR);
+        Parity::from_lcr(lcr).ok_or(Error::UnexpectedRegisterValue {
+            offset: offset::LCR,
+            value: lcr,
+        })
     }
 
     /// set parity
     pub fn set_parity(&self, parity: Parity) {
-        match parity {
-            Parity::No => unsafe { self.reg.lcr.modify(|v| (v & 0b1100_0111)) },
-            Parity::Odd => unsafe { self.reg.lcr.modify(|v| (v & 0b1100_0111) | 0b0000_1000) },
-            Parity::Even => unsafe { self.reg.lcr.modify(|v| (v & 0b1100_0111) | 0b0001_1000) },
-            Parity::Mark => unsafe { self.reg.lcr.modify(|v| (v & 0b1100_0111) | 0b0010_1000) },
-            Parity::Space => unsafe { self.reg.lcr.modify(|v| v | 0b0011_1000) },
-        }
+        self.access
+            .modify(offset::LCR, |v| (v & !LCR_PARITY) | parity.lcr())
     }
 
     /// get stop bit of used data protocol
     ///
     /// Simply return a u8 to indicate 1 or 1.5/2 bits
     pub fn get_stop_bit(&self) -> u8 {
-        ((self.reg.lcr.read() & 0b100) >> 2) + 1
+        ((self.access.read(offset::LCR) & LCR_STOP_BITS) >> 2) + 1
     }
 
     /// set stop bit, only 1 and 2 can be used as `stop_bit`
     pub fn set_stop_bit(&self, stop_bit: u8) {
+        self.try_set_stop_bit(stop_bit).expect("Invalid stop bit")
+    }
+
+    /// set stop bit, or return an error if `stop_bit` isn't 1 or 2
+    pub fn try_set_stop_bit(&self, stop_bit: u8) -> Result<(), Error> {
         match stop_bit {
-            1 => unsafe { self.reg.lcr.modify(|v| v & 0b1111_1011) },
-            2 => unsafe { self.reg.lcr.modify(|v| v | 0b0000_0100) },
-            _ => panic!("Invalid stop bit"),
+            1 => self.access.modify(offset::LCR, |v| v & !LCR_STOP_BITS),
+            2 => self.access.modify(offset::LCR, |v| v | LCR_STOP_BITS),
+            _ => return Err(Error::InvalidConfig),
         }
+        Ok(())
     }
 
     /// get word length of used data protocol
     pub fn get_word_length(&self) -> u8 {
-        (self.reg.lcr.read() & 0b11) + 5
+        (self.access.read(offset::LCR) & LCR_WORD_LENGTH) + 5
     }
 
     /// set word length, only 5..=8 can be used as `length`
     pub fn set_word_length(&self, length: u8) {
+        self.try_set_word_length(length)
+            .expect("Invalid word length")
+    }
+
+    /// set word length, or return an error if `length` isn't in 5..=8
+    pub fn try_set_word_length(&self, length: u8) -> Result<(), Error> {
         if (5..=8).contains(&length) {
-            unsafe { self.reg.lcr.modify(|v| v | (length - 5)) }
+            self.access
+                .modify(offset::LCR, |v| (v & !LCR_WORD_LENGTH) | (length - 5));
+            Ok(())
         } else {
-            panic!("Invalid word length")
+            Err(Error::InvalidConfig)
         }
     }
 
@@ -647,7 +1240,7 @@ impl<'a> MmioUart8250<'a> {
     /// > | 0   | Data Terminal Ready              |
     #[inline]
     pub fn read_mcr(&self) -> u8 {
-        self.reg.mcr.read()
+        self.access.read(offset::MCR)
     }
 
     /// Write MCR (offset + 4)
@@ -655,7 +1248,142 @@ impl<'a> MmioUart8250<'a> {
     /// Write Modem Control Register to control flow
     #[inline]
     pub fn write_mcr(&self, value: u8) {
-        unsafe { self.reg.mcr.write(value) }
+        self.access.write(offset::MCR, value)
+    }
+
+    /// Get MCR bitflags
+    #[inline]
+    pub fn mcr(&self) -> MCR {
+        MCR::from_bits_truncate(self.read_mcr())
+    }
+
+    /// Set MCR bitflags
+    #[inline]
+    pub fn set_mcr(&self, flag: MCR) {
+        self.write_mcr(flag.bits())
+    }
+
+    /// get whether data terminal ready is enabled (MCR\[0\])
+    pub fn is_data_terminal_ready_enabled(&self) -> bool {
+        self.mcr().contains(MCR::DTR)
+    }
+
+    /// toggle data terminal ready (MCR\[0\])
+    pub fn toggle_data_terminal_ready(&self) {
+        self.access.modify(offset::MCR, |v| v ^ MCR::DTR.bits())
+    }
+
+    /// enable data terminal ready (MCR\[0\])
+    pub fn enable_data_terminal_ready(&self) {
+        self.access.modify(offset::MCR, |v| v | MCR::DTR.bits())
+    }
+
+    /// disable data terminal ready (MCR\[0\])
+    pub fn disable_data_terminal_ready(&self) {
+        self.access.modify(offset::MCR, |v| v & !MCR::DTR.bits())
+    }
+
+    /// get whether request to send is enabled (MCR\[1\])
+    pub fn is_request_to_send_enabled(&self) -> bool {
+        self.mcr().contains(MCR::RTS)
+    }
+
+    /// toggle request to send (MCR\[1\])
+    pub fn toggle_request_to_send(&self) {
+        self.access.modify(offset::MCR, |v| v ^ MCR::RTS.bits())
+    }
+
+    /// enable request to send (MCR\[1\])
+    pub fn enable_request_to_send(&self) {
+        self.access.modify(offset::MCR, |v| v | MCR::RTS.bits())
+    }
+
+    /// disable request to send (MCR\[1\])
+    pub fn disable_request_to_send(&self) {
+        self.access.modify(offset::MCR, |v| v & !MCR::RTS.bits())
+    }
+
+    /// get whether auxiliary output 1 is enabled (MCR\[2\])
+    pub fn is_auxiliary_output_1_enabled(&self) -> bool {
+        self.mcr().contains(MCR::OUT1)
+    }
+
+    /// toggle auxiliary output 1 (MCR\[2\])
+    pub fn toggle_auxiliary_output_1(&self) {
+        self.access.modify(offset::MCR, |v| v ^ MCR::OUT1.bits())
+    }
+
+    /// enable auxiliary output 1 (MCR\[2\])
+    pub fn enable_auxiliary_output_1(&self) {
+        self.access.modify(offset::MCR, |v| v | MCR::OUT1.bits())
+    }
+
+    /// disable auxiliary output 1 (MCR\[2\])
+    pub fn disable_auxiliary_output_1(&self) {
+        self.access.modify(offset::MCR, |v| v & !MCR::OUT1.bits())
+    }
+
+    /// get whether auxiliary output 2 is enabled (MCR\[3\])
+    pub fn is_auxiliary_output_2_enabled(&self) -> bool {
+        self.mcr().contains(MCR::OUT2)
+    }
+
+    /// toggle auxiliary output 2 (MCR\[3\])
+    pub fn toggle_auxiliary_output_2(&self) {
+        self.access.modify(offset::MCR, |v| v ^ MCR::OUT2.bits())
+    }
+
+    /// enable auxiliary output 2 (MCR\[3\])
+    pub fn enable_auxiliary_output_2(&self) {
+        self.access.modify(offset::MCR, |v| v | MCR::OUT2.bits())
+    }
+
+    /// disable auxiliary output 2 (MCR\[3\])
+    pub fn disable_auxiliary_output_2(&self) {
+        self.access.modify(offset::MCR, |v| v & !MCR::OUT2.bits())
+    }
+
+    /// get whether loopback mode is enabled (MCR\[4\])
+    pub fn is_loopback_mode_enabled(&self) -> bool {
+        self.mcr().contains(MCR::LOOPBACK)
+    }
+
+    /// toggle loopback mode (MCR\[4\])
+    pub fn toggle_loopback_mode(&self) {
+        self.access
+            .modify(offset::MCR, |v| v ^ MCR::LOOPBACK.bits())
+    }
+
+    /// enable loopback mode (MCR\[4\])
+    pub fn enable_loopback_mode(&self) {
+        self.access
+            .modify(offset::MCR, |v| v | MCR::LOOPBACK.bits())
+    }
+
+    /// disable loopback mode (MCR\[4\])
+    pub fn disable_loopback_mode(&self) {
+        self.access
+            .modify(offset::MCR, |v| v & !MCR::LOOPBACK.bits())
+    }
+
+    /// get whether autoflow control (16750) is enabled (MCR\[5\])
+    pub fn is_auto_flow_control_enabled(&self) -> bool {
+        self.mcr().contains(MCR::AFE)
+    }
+
+    /// toggle autoflow control (16750) (MCR\[5\])
+    pub fn toggle_auto_flow_control(&self) {
+        self.access.modify(offset::MCR, |v| v ^ MCR::AFE.bits())
+    }
+
+    /// enable autoflow control (16750) (MCR\[5\])
+    pub fn enable_auto_flow_control(&self) {
+        self.access.modify(offset::MCR, |v| v | MCR::AFE.bits())
+    }
+
+    /// disable autoflow control (16750) (MCR\[5\])
+    pub fn disable_auto_flow_control(&self) {
+        self.access.modify(offset::MCR, |v| v & !MCR::AFE.bits())
     }
 
     /// Read LSR (offset + 5)
@@ -674,9 +1402,19 @@ impl<'a> MmioUart8250<'a> {
     /// > | 2   | Parity Error                       |
     /// > | 1   | Overrun Error                      |
     /// > | 0   | Data Ready                         |
+    ///
+    /// Reading LSR clears its error bits, so they are also added to the errors returned by
+    /// [`take_line_errors`](Self::take_line_errors).
     #[inline]
     pub fn read_lsr(&self) -> u8 {
-        self.reg.lsr.read()
+        let lsr = self.access.read(offset::LSR);
+        if lsr & LINE_ERRORS.bits() != 0 {
+            self.locked(|| {
+                self.line_errors
+                    .set(self.line_errors.get() | (lsr & LINE_ERRORS.bits()))
+            });
+        }
+        lsr
     }
 
     /// Get LSR bitflags
@@ -685,6 +1423,37 @@ impl<'a> MmioUart8250<'a> {
         LSR::from_bits_truncate(self.read_lsr())
     }
 
+    /// Get LSR bitflags without adding its errors to [`line_errors`](Self::line_errors), for
+    /// callers which return the errors themselves or cause them on purpose
+    #[inline]
+    pub(crate) fn raw_lsr(&self) -> LSR {
+        LSR::from_bits_truncate(self.access.read(offset::LSR))
+    }
+
+    /// Read LSR once, to check several of its bits
+    pub fn line_status(&self) -> LineStatus {
+        LineStatus::from(self.lsr())
+    }
+
+    /// Get the error bits (OE, PE, FE and BI) of every LSR read since the last
+    /// [`take_line_errors`](Self::take_line_errors)
+    pub fn line_errors(&self) -> LSR {
+        LSR::from_bits_truncate(self.line_errors.get())
+    }
+
+    /// Get the error bits of every LSR read since the last call, and clear them
+    ///
+    /// Errors are collected by the LSR reads which don't return them, such as [`lsr`](Self::lsr)
+    /// and [`is_data_ready`](Self::is_data_ready), so an error cleared by checking something else
+    /// in LSR is still seen here. Errors returned by a read, e.g. of `embedded_io::Read` or
+    /// [`dispatch_interrupts`](Self::dispatch_interrupts), aren't collected, and neither are the
+    /// ones caused on purpose by [`probe`](Self::probe), [`self_test`](Self::self_test),
+    /// [`autobaud`](Self::autobaud) and [`measure_clock`](Self::measure_clock).
+    /// [`LineError::from_lsr`](crate::LineError::from_lsr) gives the most significant one.
+    pub fn take_line_errors(&self) -> LSR {
+        LSR::from_bits_truncate(self.locked(|| self.line_errors.replace(0)))
+    }
+
     /// get whether there is an error in received FIFO
     pub fn is_received_fifo_error(&self) -> bool {
         self.lsr().contains(LSR::RFE)
@@ -700,22 +1469,43 @@ impl<'a> MmioUart8250<'a> {
         self.lsr().contains(LSR::THRE)
     }
 
+    /// get whether a break was received (LSR\[4\])
+    ///
+    /// Reading LSR clears the other error bits too, see
+    /// [`read_event`](Self::read_event) to get the break with its character, or
+    /// [`line_status`](Self::line_status) to check several bits with one read.
     pub fn is_break_interrupt(&self) -> bool {
         self.lsr().contains(LSR::BI)
     }
 
+    /// get whether the received character had no valid stop bit (LSR\[3\])
+    ///
+    /// Each call reads LSR, which clears its error bits; use [`line_status`](Self::line_status)
+    /// to check several bits with one read, or [`take_line_errors`](Self::take_line_errors).
     pub fn is_framing_error(&self) -> bool {
         self.lsr().contains(LSR::FE)
     }
 
+    /// get whether the received character had the wrong parity (LSR\[2\])
+    ///
+    /// Each call reads LSR, which clears its error bits; use [`line_status`](Self::line_status)
+    /// to check several bits with one read, or [`take_line_errors`](Self::take_line_errors).
     pub fn is_parity_error(&self) -> bool {
         self.lsr().contains(LSR::PE)
     }
 
+    /// get whether a received character was lost (LSR\[1\])
+    ///
+    /// Each call reads LSR, which clears its error bits; use [`line_status`](Self::line_status)
+    /// to check several bits with one read, or [`take_line_errors`](Self::take_line_errors).
     pub fn is_overrun_error(&self) -> bool {
         self.lsr().contains(LSR::OE)
     }
 
+    /// get whether a character is waiting (LSR\[0\])
+    ///
+    /// Each call reads LSR, which clears its error bits; use [`line_status`](Self::line_status)
+    /// to check several bits with one read, or [`take_line_errors`](Self::take_line_errors).
     pub fn is_data_ready(&self) -> bool {
         self.lsr().contains(LSR::DR)
     }
@@ -738,7 +1528,7 @@ impl<'a> MmioUart8250<'a> {
     /// > | 0   | Delta Clear To Send          |
     #[inline]
     pub fn read_msr(&self) -> u8 {
-        self.reg.msr.read()
+        self.access.read(offset::MSR)
     }
 
     /// Get MSR bitflags
@@ -781,12 +1571,12 @@ impl<'a> MmioUart8250<'a> {
 
     #[inline]
     pub fn read_sr(&self) -> u8 {
-        self.reg.scratch.read()
+        self.access.read(offset::SCR)
     }
 
     #[inline]
     pub fn write_sr(&self, value: u8) {
-        unsafe { self.reg.scratch.write(value) }
+        self.access.write(offset::SCR, value)
     }
 }
 
@@ -794,7 +1584,7 @@ impl<'a> MmioUart8250<'a> {
 ///
 /// A simple implementation, may be changed in the future
 #[cfg(feature = "fmt")]
-impl<'a> fmt::Write for MmioUart8250<'a> {
+impl<A: RegisterAccess> fmt::Write for Uart8250<A> {
     fn write_str(&mut self, s: &str) -> fmt::Result {
         for c in s.as_bytes() {
             self.write_thr(*c);
@@ -802,3 +1592,132 @@ impl<'a> fmt::Write for MmioUart8250<'a> {
         Ok(())
     }
 }
+
+#[cfg(feature = "embedded-hal-nb")]
+impl<A: RegisterAccess> embedded_hal_nb::serial::ErrorType for Uart8250<A> {
+    type Error = LineError;
+}
+
+/// ## embedded_hal_nb::serial::Read
+///
+/// Only LSR is read, as reading IIR would clear a pending THRE interrupt.
+/// A line error is returned before the character it came with, which is returned by the next read.
+#[cfg(feature = "embedded-hal-nb")]
+impl<A: RegisterAccess> embedded_hal_nb::serial::Read for Uart8250<A> {
+    fn read(&mut self) -> nb::Result<u8, Self::Error> {
+        let lsr = self.raw_lsr() | self.take_rx_errors();
+        if let Some(error) = LineError::from_lsr(lsr) {
+            Err(nb::Error::Other(error))
+        } else if lsr.contains(LSR::DR) {
+            Ok(self.read_rbr())
+        } else {
+            Err(nb::Error::WouldBlock)
+        }
+    }
+}
+
+/// ## embedded_hal_nb::serial::Write
+///
+/// LSR error bits read while waiting are kept for the next read.
+#[cfg(feature = "embedded-hal-nb")]
+impl<A: RegisterAccess> embedded_hal_nb::serial::Write for Uart8250<A> {
+    fn write(&mut self, word: u8) -> nb::Result<(), Self::Error> {
+        if self.tx_lsr().contains(LSR::THRE) {
+            self.write_thr(word);
+            Ok(())
+        } else {
+            Err(nb::Error::WouldBlock)
+        }
+    }
+
+    fn flush(&mut self) -> nb::Result<(), Self::Error> {
+        if self.tx_lsr().contains(LSR::DHRE) {
+            Ok(())
+        } else {
+            Err(nb::Error::WouldBlock)
+        }
+    }
+}
+
+#[cfg(feature = "embedded-io")]
+impl<A: RegisterAccess> embedded_io::ErrorType for Uart8250<A> {
+    type Error = LineError;
+}
+
+/// ## embedded_io::Read
+///
+/// Blocks until a character is received, then reads as many as are ready.
+/// A line error ends the read, and is returned by the next read if some bytes were already read.
+#[cfg(feature = "embedded-io")]
+impl<A: RegisterAccess> embedded_io::Read for Uart8250<A> {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize, Self::Error> {
+        let mut count = 0;
+        while count < buf.len() {
+            let lsr = self.raw_lsr() | self.take_rx_errors();
+            if let Some(error) = LineError::from_lsr(lsr) {
+                if count == 0 {
+                    return Err(error);
+                }
+                self.keep_rx_errors(lsr);
+                break;
+            } else if lsr.contains(LSR::DR) {
+                buf[count] = self.read_rbr();
+                count += 1;
+            } else if count > 0 {
+                break;
+            } else {
+                core::hint::spin_loop();
+            }
+        }
+        Ok(count)
+    }
+}
+
+/// ## embedded_io::ReadReady
+#[cfg(feature = "embedded-io")]
+impl<A: RegisterAccess> embedded_io::ReadReady for Uart8250<A> {
+    fn read_ready(&mut self) -> Result<bool, Self::Error> {
+        let lsr = self.raw_lsr() | self.take_rx_errors();
+        match LineError::from_lsr(lsr) {
+            Some(error) => Err(error),
+            None => Ok(lsr.contains(LSR::DR)),
+        }
+    }
+}
+
+/// ## embedded_io::Write
+///
+/// Blocks until THR is empty, then writes as many bytes as THR takes.
+/// LSR error bits read while waiting are kept for the next read.
+#[cfg(feature = "embedded-io")]
+impl<A: RegisterAccess> embedded_io::Write for Uart8250<A> {
+    fn write(&mut self, buf: &[u8]) -> Result<usize, Self::Error> {
+        let mut count = 0;
+        while count < buf.len() {
+            if self.tx_lsr().contains(LSR::THRE) {
+                self.write_thr(buf[count]);
+                count += 1;
+            } else if count > 0 {
+                break;
+            } else {
+                core::hint::spin_loop();
+            }
+        }
+        Ok(count)
+    }
+
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        while !self.tx_lsr().contains(LSR::DHRE) {
+            core::hint::spin_loop();
+        }
+        Ok(())
+    }
+}
+
+/// ## embedded_io::WriteReady
+#[cfg(feature = "embedded-io")]
+impl<A: RegisterAccess> embedded_io::WriteReady for Uart8250<A> {
+    fn write_ready(&mut self) -> Result<bool, Self::Error> {
+        Ok(self.tx_lsr().contains(LSR::THRE))
+    }
+}
@@ -0,0 +1,151 @@
+use std::future::Future;
+use std::pin::pin;
+use std::sync::atomic::{AtomicBool, AtomicU8, AtomicUsize, Ordering};
+use std::sync::{Arc, Barrier};
+use std::task::{Context, Poll, Wake, Waker};
+use std::thread;
+
+use uart8250::access::RegisterAccess;
+use uart8250::asynch::AsyncUart8250;
+use uart8250::model::{Chip, Model};
+use uart8250::{LineError, Uart8250, IER};
+
+#[derive(Default)]
+struct CountingWaker(AtomicUsize);
+
+impl Wake for CountingWaker {
+    fn wake(self: Arc<Self>) {
+        self.0.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+#[test]
+fn read_waits_for_interrupt() {
+    let model = Model::new(Chip::U16550A);
+    let uart = AsyncUart8250::new(Uart8250::from_access(&model));
+    uart.init(1_843_200, 115200);
+    assert_eq!(model.ier(), IER::empty());
+
+    let counter = Arc::new(CountingWaker::default());
+    let waker = Waker::from(counter.clone());
+    let mut cx = Context::from_waker(&waker);
+    let mut buf = [0; 4];
+    {
+        let mut read = pin!(uart.read(&mut buf));
+
+        assert!(read.as_mut().poll(&mut cx).is_pending());
+        assert_eq!(model.ier(), IER::RDAI | IER::RLSI);
+        assert!(!model.interrupt());
+
+        model.receive_all(b"ab");
+        assert!(model.interrupt());
+        uart.handle_interrupt();
+        assert!(!model.interrupt());
+        assert_eq!(counter.0.load(Ordering::SeqCst), 1);
+
+        assert_eq!(read.as_mut().poll(&mut cx), Poll::Ready(Ok(2)));
+    }
+    assert_eq!(&buf[..2], b"ab");
+}
+
+#[test]
+fn write_waits_for_thre() {
+    let model = Model::new(Chip::U16450);
+    let uart = AsyncUart8250::new(Uart8250::from_access(&model));
+    uart.init(1_843_200, 115200);
+    model.hold_transmitter(true);
+
+    let counter = Arc::new(CountingWaker::default());
+    let waker = Waker::from(counter.clone());
+    let mut cx = Context::from_waker(&waker);
+
+    assert_eq!(pin!(uart.write(b"ab")).poll(&mut cx), Poll::Ready(Ok(1)));
+    let mut write = pin!(uart.write(b"b"));
+    assert!(write.as_mut().poll(&mut cx).is_pending());
+    assert_eq!(model.ier(), IER::THREI);
+
+    model.shift_out();
+    uart.handle_interrupt();
+    assert_eq!(counter.0.load(Ordering::SeqCst), 1);
+    assert_eq!(model.ier(), IER::empty());
+    assert_eq!(write.as_mut().poll(&mut cx), Poll::Ready(Ok(1)));
+}
+
+#[test]
+fn write_keeps_errors_for_read() {
+    let model = Model::new(Chip::U16550A);
+    let uart = AsyncUart8250::new(Uart8250::from_access(&model));
+    uart.init(1_843_200, 115200);
+
+    let waker = Waker::from(Arc::new(CountingWaker::default()));
+    let mut cx = Context::from_waker(&waker);
+    let mut buf = [0; 4];
+
+    model.receive_parity_error(b'a');
+    assert_eq!(pin!(uart.write(b"x")).poll(&mut cx), Poll::Ready(Ok(1)));
+    assert_eq!(
+        pin!(uart.read(&mut buf)).poll(&mut cx),
+        Poll::Ready(Err(LineError::Parity))
+    );
+    assert_eq!(pin!(uart.read(&mut buf)).poll(&mut cx), Poll::Ready(Ok(1)));
+
+    model.receive_framing_error(b'b');
+    assert_eq!(pin!(uart.flush()).poll(&mut cx), Poll::Ready(Ok(())));
+    assert_eq!(
+        pin!(uart.read(&mut buf)).poll(&mut cx),
+        Poll::Ready(Err(LineError::Framing))
+    );
+}
+
+/// Registers which can be shared between threads, checking that accesses don't overlap
+#[derive(Default)]
+struct Shared {
+    registers: [AtomicU8; 8],
+    busy: AtomicBool,
+}
+
+impl Shared {
+    fn access<R>(&self, f: impl FnOnce() -> R) -> R {
+        assert!(!self.busy.swap(true, Ordering::SeqCst));
+        thread::yield_now();
+        let result = f();
+        self.busy.store(false, Ordering::SeqCst);
+        result
+    }
+}
+
+impl RegisterAccess for Shared {
+    fn read(&self, offset: usize) -> u8 {
+        self.access(|| self.registers[offset].load(Ordering::SeqCst))
+    }
+
+    fn write(&self, offset: usize, value: u8) {
+        self.access(|| self.registers[offset].store(value, Ordering::SeqCst))
+    }
+}
+
+#[test]
+fn interrupt_handler_and_task_take_turns() {
+    let registers = Shared::default();
+    let uart = AsyncUart8250::new(Uart8250::from_access(&registers));
+    let start = Barrier::new(2);
+
+    thread::scope(|scope| {
+        scope.spawn(|| {
+            start.wait();
+            for _ in 0..200 {
+                uart.handle_interrupt();
+            }
+        });
+        scope.spawn(|| {
+            let waker = Waker::from(Arc::new(CountingWaker::default()));
+            let mut cx = Context::from_waker(&waker);
+            let mut buf = [0; 4];
+            start.wait();
+            for _ in 0..200 {
+                assert!(pin!(uart.read(&mut buf)).poll(&mut cx).is_pending());
+                uart.with_uart(|uart| uart.read_msr());
+            }
+        });
+    });
+}
@@ -0,0 +1,96 @@
+use uart8250::access::RegisterAccess;
+use uart8250::autobaud::{AutobaudConfig, AutobaudMode};
+use uart8250::model::{Chip, Model};
+use uart8250::registers::offset;
+use uart8250::{Error, Uart8250};
+
+/// A sender repeating `sync` at the baud rate given by `divisor`, which the receiver only gets
+/// intact when its divisor matches
+struct Sender<'a> {
+    model: &'a Model,
+    divisor: Option<u16>,
+    sync: u8,
+}
+
+impl RegisterAccess for Sender<'_> {
+    fn read(&self, offset: usize) -> u8 {
+        if offset == offset::LSR && self.model.rx_len() == 0 {
+            match self.divisor {
+                Some(divisor) if divisor == self.model.divisor() => self.model.receive(self.sync),
+                Some(_) => self.model.receive_framing_error(!self.sync),
+                None => {}
+            }
+        }
+        self.model.read(offset)
+    }
+
+    fn write(&self, offset: usize, value: u8) {
+        self.model.write(offset, value)
+    }
+}
+
+#[test]
+fn detects_and_programs() {
+    let model = Model::new(Chip::U16550A);
+    let sender = Sender {
+        model: &model,
+        divisor: Some(48),
+        sync: 0x55,
+    };
+    let uart = Uart8250::from_access(&sender);
+    uart.init(1_843_200, 115200);
+
+    let plan = uart
+        .autobaud(
+            1_843_200,
+            &AutobaudConfig::new(Some(0x55)),
+            AutobaudMode::Program,
+        )
+        .unwrap();
+    assert_eq!(plan.actual_baud_rate, 2400);
+    assert_eq!(model.divisor(), 48);
+}
+
+#[test]
+fn passive_restores_divisor() {
+    let model = Model::new(Chip::U16550A);
+    let sender = Sender {
+        model: &model,
+        divisor: Some(12),
+        sync: b'\r',
+    };
+    let uart = Uart8250::from_access(&sender);
+    uart.init(1_843_200, 115200);
+
+    let plan = uart
+        .autobaud(
+            1_843_200,
+            &AutobaudConfig::new(Some(b'\r')),
+            AutobaudMode::Passive,
+        )
+        .unwrap();
+    assert_eq!(plan.actual_baud_rate, 9600);
+    assert_eq!(model.divisor(), 1);
+}
+
+#[test]
+fn nothing_received() {
+    let model = Model::new(Chip::U16550A);
+    let sender = Sender {
+        model: &model,
+        divisor: None,
+        sync: 0x55,
+    };
+    let uart = Uart8250::from_access(&sender);
+    uart.init(1_843_200, 115200);
+
+    let config = AutobaudConfig {
+        polls: 10,
+        ..AutobaudConfig::new(None)
+    };
+    assert_eq!(
+        uart.autobaud(1_843_200, &config, AutobaudMode::Program),
+        Err(Error::NotDetected)
+    );
+    assert_eq!(model.divisor(), 1);
+}
@@ -0,0 +1,191 @@
+use std::cell::Cell;
+
+use uart8250::access::RegisterAccess;
+use uart8250::model::{Chip, Model};
+use uart8250::registers::offset;
+use uart8250::{BaudPlan, DivisorExtension, DivisorVariant, Error, LineConfig, Uart8250};
+
+#[test]
+fn nearest_divisor() {
+    // 48 MHz / 16 / 115200 = 26.04
+    let plan = BaudPlan::new(48_000_000, 115200).unwrap();
+    assert_eq!(plan.divisor, 26);
+    assert_eq!(plan.actual_baud_rate, 115385);
+
+    // 22.1184 MHz / 16 / 300000 = 4.608, rounds up
+    let plan = BaudPlan::new(22_118_400, 300000).unwrap();
+    assert_eq!(plan.divisor, 5);
+    assert_eq!(plan.actual_baud_rate, 276480);
+    assert!(plan.error_percent() < -7.8 && plan.error_percent() > -7.9);
+}
+
+#[test]
+fn rejected_plans() {
+    assert_eq!(BaudPlan::new(1_843_200, 0), Err(Error::InvalidConfig));
+    assert_eq!(
+        BaudPlan::new(100_000_000, 50),
+        Err(Error::UnsupportedDivisor)
+    );
+    assert_eq!(
+        BaudPlan::with_tolerance(1_843_200, 230400, 20_000),
+        Err(Error::UnsupportedDivisor)
+    );
+
+    // Too large to multiply out, rather than overflowing
+    for variant in [
+        DivisorVariant::Standard,
+        DivisorVariant::DesignWare { dlf_bits: 4 },
+        DivisorVariant::NxpLpc,
+        DivisorVariant::Ox16C950,
+        DivisorVariant::MediaTek,
+    ] {
+        assert_eq!(
+            BaudPlan::for_variant(variant, usize::MAX, usize::MAX),
+            Err(Error::UnsupportedDivisor)
+        );
+    }
+}
+
+#[test]
+fn achievable_rates() {
+    let rates: Vec<usize> = BaudPlan::achievable(1_843_200, 0)
+        .map(|plan| plan.actual_baud_rate)
+        .collect();
+    assert_eq!(rates.first(), Some(&50));
+    assert_eq!(rates.last(), Some(&115200));
+    assert!(!rates.contains(&134));
+}
+
+#[test]
+fn set_divisor_rounds() {
+    let model = Model::new(Chip::U16550A);
+    let uart = Uart8250::from_access(&model);
+    uart.set_divisor(48_000_000, 115200);
+    assert_eq!(model.divisor(), 26);
+
+    assert_eq!(
+        uart.try_set_divisor(48_000_000, 2_000_000, 20_000),
+        Err(Error::UnsupportedDivisor)
+    );
+    assert_eq!(model.divisor(), 26);
+    assert!(!uart.is_divisor_latch_accessible());
+}
+
+#[test]
+fn extended_divisors() {
+    // MIDI from a 1.8432 MHz clock
+    let plan = BaudPlan::for_variant(DivisorVariant::DesignWare { dlf_bits: 4 }, 1_843_200, 31250)
+        .unwrap();
+    assert_eq!(plan.divisor, 3);
+    assert_eq!(
+        plan.extension,
+        DivisorExtension::Fraction { dlf: 11, bits: 4 }
+    );
+    assert_eq!(plan.actual_baud_rate, 31241);
+    assert_eq!(plan.error_ppm, -298);
+
+    let plan = BaudPlan::for_variant(DivisorVariant::NxpLpc, 12_000_000, 115200).unwrap();
+    assert!(plan.divisor >= 3);
+    assert!(plan.error_ppm.abs() < 2000);
+
+    let plan = BaudPlan::for_variant(DivisorVariant::Ox16C950, 62_500_000, 3_125_000).unwrap();
+    assert_eq!(plan.divisor, 2);
+    assert_eq!(plan.extension, DivisorExtension::SamplesPerBit(10));
+    assert_eq!(plan.error_ppm, 0);
+
+    let plan = BaudPlan::for_variant(DivisorVariant::MediaTek, 26_000_000, 921600).unwrap();
+    assert_eq!(plan.divisor, 1);
+    assert_eq!(plan.extension, DivisorExtension::SampleCount(28));
+    assert_eq!(plan.actual_baud_rate, 928571);
+}
+
+/// Registers without side effects, and DLL/DLH banked by DLAB
+struct Registers {
+    divisor_latch: [Cell<u8>; 2],
+    registers: [Cell<u8>; 0x40],
+}
+
+impl Default for Registers {
+    fn default() -> Self {
+        Self {
+            divisor_latch: Default::default(),
+            registers: core::array::from_fn(|_| Cell::new(0)),
+        }
+    }
+}
+
+impl RegisterAccess for Registers {
+    fn read(&self, offset: usize) -> u8 {
+        match offset {
+            0 | 1 if self.registers[offset::LCR].get() & 0x80 != 0 => {
+                self.divisor_latch[offset].get()
+            }
+            _ => self.registers[offset].get(),
+        }
+    }
+
+    fn write(&self, offset: usize, value: u8) {
+        match offset {
+            0 | 1 if self.registers[offset::LCR].get() & 0x80 != 0 => {
+                self.divisor_latch[offset].set(value)
+            }
+            _ => self.registers[offset].set(value),
+        }
+    }
+}
+
+#[test]
+fn extended_divisor_registers() {
+    let registers = Registers::default();
+    let uart = Uart8250::from_access(&registers)
+        .with_divisor_variant(DivisorVariant::DesignWare { dlf_bits: 4 });
+    uart.apply_line_config(1_843_200, &LineConfig::new(31250));
+    assert_eq!(registers.divisor_latch[0].get(), 3);
+    assert_eq!(registers.registers[offset::DW_DLF].get(), 11);
+    assert_eq!(uart.line_config(1_843_200).baud_rate, 31241);
+
+    let registers = Registers::default();
+    let uart = Uart8250::from_access(&registers).with_divisor_variant(DivisorVariant::MediaTek);
+    uart.set_divisor(26_000_000, 921600);
+    assert_eq!(registers.divisor_latch[0].get(), 1);
+    assert_eq!(registers.registers[offset::MTK_HIGHSPEED].get(), 3);
+    assert_eq!(registers.registers[offset::MTK_SAMPLE_COUNT].get(), 27);
+    assert_eq!(registers.registers[offset::MTK_SAMPLE_POINT].get(), 13);
+    assert_eq!(uart.line_config(26_000_000).baud_rate, 928571);
+}
+
+#[test]
+fn invalid_plans_are_not_written() {
+    let registers = Registers::default();
+    let uart = Uart8250::from_access(&registers).with_divisor_variant(DivisorVariant::MediaTek);
+    for samples in [0, 1, 257] {
+        let plan = BaudPlan {
+            divisor: 1,
+            extension: DivisorExtension::SampleCount(samples),
+            actual_baud_rate: 0,
+            error_ppm: 0,
+        };
+        assert_eq!(plan.validate(), Err(Error::InvalidConfig));
+        assert_eq!(uart.try_write_baud_plan(&plan), Err(Error::InvalidConfig));
+    }
+    assert_eq!(registers.divisor_latch[0].get(), 0);
+    assert_eq!(registers.registers[offset::MTK_HIGHSPEED].get(), 0);
+
+    // A sample count register of 0 reads back as a plan which can be written again
+    registers.registers[offset::MTK_HIGHSPEED].set(3);
+    let plan = uart.read_baud_plan(26_000_000);
+    assert_eq!(plan.extension, DivisorExtension::SampleCount(2));
+    uart.write_baud_plan(&plan);
+}
+
+#[test]
+fn reading_tcr_keeps_acr() {
+    let registers = Registers::default();
+    let uart = Uart8250::from_access(&registers).with_divisor_variant(DivisorVariant::Ox16C950);
+    uart.set_acr(0x20);
+    // TCR is read with ICR reads enabled in ACR, which is put back afterwards
+    uart.read_baud_plan(62_500_000);
+    assert_eq!(registers.registers[offset::SCR].get(), offset::icr::ACR);
+    assert_eq!(registers.registers[offset::ICR].get(), 0x20);
+    assert_eq!(uart.acr(), 0x20);
+}
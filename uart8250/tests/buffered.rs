@@ -0,0 +1,142 @@
+use std::sync::atomic::{AtomicBool, AtomicU8, Ordering};
+use std::sync::Barrier;
+use std::thread;
+
+use uart8250::access::RegisterAccess;
+use uart8250::buffered::BufferedUart8250;
+use uart8250::model::{Chip, Model};
+use uart8250::registers::offset;
+use uart8250::{LineError, Uart8250, FCR, IER};
+
+#[test]
+fn receive_through_buffer() {
+    let model = Model::new(Chip::U16550A);
+    let uart: BufferedUart8250<_, 32, 32> = BufferedUart8250::new(Uart8250::from_access(&model));
+    uart.init(1_843_200, 115200);
+    assert_eq!(model.ier(), IER::RDAI | IER::RLSI);
+
+    model.receive_all(b"hello");
+    assert!(model.interrupt());
+    uart.handle_interrupt();
+    assert!(!model.interrupt());
+    assert_eq!(uart.rx_len(), 5);
+
+    let mut buf = [0; 3];
+    assert_eq!(uart.read(&mut buf), Ok(3));
+    assert_eq!(&buf, b"hel");
+    assert_eq!(uart.read(&mut buf), Ok(2));
+    assert_eq!(&buf[..2], b"lo");
+    assert_eq!(uart.read(&mut buf), Ok(0));
+}
+
+#[test]
+fn transmit_arms_threi_while_queued() {
+    let model = Model::new(Chip::U16550A);
+    model.hold_transmitter(true);
+    let uart: BufferedUart8250<_, 32, 32> = BufferedUart8250::new(Uart8250::from_access(&model));
+    uart.init(1_843_200, 115200);
+
+    let data: Vec<u8> = (0..40).collect();
+    assert_eq!(uart.write(&data), 32);
+    assert_eq!(uart.tx_len(), 32);
+    assert!(model.ier().contains(IER::THREI));
+
+    let mut sent = Vec::new();
+    while model.interrupt() {
+        uart.handle_interrupt();
+        assert!(model.tx_len() <= 16);
+        while model.shift_out().is_some() {}
+        while let Some(byte) = model.transmitted() {
+            sent.push(byte);
+        }
+    }
+    assert_eq!(sent, data[..32]);
+    assert_eq!(uart.tx_len(), 0);
+    assert!(!model.ier().contains(IER::THREI));
+
+    // Nothing queued, so no THRE interrupt storm
+    assert!(!model.interrupt());
+    assert_eq!(uart.write(&[]), 0);
+    assert!(!model.ier().contains(IER::THREI));
+}
+
+#[test]
+fn errors_are_reported_once() {
+    let model = Model::new(Chip::U16550A);
+    let uart: BufferedUart8250<_, 4, 4> =
+        BufferedUart8250::new(Uart8250::from_access(&model)).with_tx_burst(1);
+    uart.init(1_843_200, 115200);
+
+    model.receive_parity_error(b'a');
+    model.receive_all(b"bcdef");
+    uart.handle_interrupt();
+
+    let mut buf = [0; 8];
+    assert_eq!(uart.read(&mut buf), Err(LineError::Parity));
+    assert_eq!(uart.read(&mut buf), Ok(4));
+    assert_eq!(&buf[..4], b"abcd");
+
+    model.receive_all(b"ghijk");
+    uart.handle_interrupt();
+    assert_eq!(uart.read(&mut buf), Err(LineError::Overrun));
+    assert_eq!(uart.read(&mut buf), Ok(4));
+    assert_eq!(&buf[..4], b"ghij");
+}
+
+/// Registers which can be shared between threads, checking that accesses don't overlap
+#[derive(Default)]
+struct Shared {
+    registers: [AtomicU8; 8],
+    busy: AtomicBool,
+}
+
+impl Shared {
+    fn access<R>(&self, f: impl FnOnce() -> R) -> R {
+        assert!(!self.busy.swap(true, Ordering::SeqCst));
+        thread::yield_now();
+        let result = f();
+        self.busy.store(false, Ordering::SeqCst);
+        result
+    }
+}
+
+impl RegisterAccess for Shared {
+    fn read(&self, offset: usize) -> u8 {
+        self.access(|| self.registers[offset].load(Ordering::SeqCst))
+    }
+
+    fn write(&self, offset: usize, value: u8) {
+        self.access(|| self.registers[offset].store(value, Ordering::SeqCst))
+    }
+}
+
+#[test]
+fn registers_are_accessed_in_critical_sections() {
+    let registers = Shared::default();
+    let uart: BufferedUart8250<_, 32, 32> =
+        BufferedUart8250::new(Uart8250::from_access(&registers));
+    let start = Barrier::new(3);
+
+    thread::scope(|scope| {
+        scope.spawn(|| {
+            start.wait();
+            for _ in 0..200 {
+                uart.handle_interrupt();
+            }
+        });
+        for fcr in [FCR::ENABLE, FCR::ENABLE | FCR::RX_TRIGGER_MSB] {
+            let (uart, start) = (&uart, &start);
+            scope.spawn(move || {
+                start.wait();
+                for _ in 0..200 {
+                    uart.with_uart(|uart| uart.set_fcr(fcr));
+                    uart.write(b"a");
+                }
+            });
+        }
+    });
+    assert_eq!(
+        uart.with_uart(|uart| uart.fcr()).bits(),
+        registers.registers[offset::FCR].load(Ordering::SeqCst)
+    );
+}
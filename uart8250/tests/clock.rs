@@ -0,0 +1,101 @@
+use std::cell::Cell;
+
+use uart8250::access::RegisterAccess;
+use uart8250::clock::{nearest_common_clock, Monotonic};
+use uart8250::model::{Chip, Model};
+use uart8250::registers::offset;
+use uart8250::{Error, Uart8250};
+
+/// A UART with a `clock` Hz input clock, which takes `step` ns per register access and sends
+/// characters in real time, 10 bits each
+struct Timed<'a> {
+    model: &'a Model,
+    clock: u64,
+    step: u64,
+    /// Nanoseconds
+    now: Cell<u64>,
+    /// When the character being sent is done
+    done: Cell<u64>,
+}
+
+impl<'a> Timed<'a> {
+    fn new(model: &'a Model, clock: u64, step: u64) -> Self {
+        model.hold_transmitter(true);
+        Self {
+            model,
+            clock,
+            step,
+            now: Cell::new(0),
+            done: Cell::new(0),
+        }
+    }
+
+    fn character_time(&self) -> u64 {
+        10 * 16 * self.model.divisor() as u64 * 1_000_000_000 / self.clock
+    }
+
+    fn tick(&self) {
+        let now = self.now.get() + self.step;
+        self.now.set(now);
+        if self.model.tx_len() > 0 && now >= self.done.get() {
+            self.model.shift_out();
+            self.done.set(now + self.character_time());
+        }
+    }
+}
+
+impl RegisterAccess for Timed<'_> {
+    fn read(&self, offset: usize) -> u8 {
+        self.tick();
+        self.model.read(offset)
+    }
+
+    fn write(&self, offset: usize, value: u8) {
+        self.tick();
+        if offset == offset::THR && self.model.lcr() & 0x80 == 0 && self.model.tx_len() == 0 {
+            self.done.set(self.now.get() + self.character_time());
+        }
+        self.model.write(offset, value)
+    }
+}
+
+impl Monotonic for Timed<'_> {
+    fn now(&self) -> u64 {
+        self.now.get()
+    }
+
+    fn ticks_per_second(&self) -> u64 {
+        1_000_000_000
+    }
+}
+
+#[test]
+fn measures_clock() {
+    let model = Model::new(Chip::U16550A);
+    let timed = Timed::new(&model, 24_000_000, 40);
+    let uart = Uart8250::from_access(&timed);
+    uart.init(24_000_000, 115200);
+    let mcr = model.mcr();
+
+    let clock = uart.measure_clock(&timed, 4, 100).unwrap();
+    assert!(clock.abs_diff(24_000_000) < 24_000_000 / 100, "{}", clock);
+    assert_eq!(nearest_common_clock(clock, 20_000), Some(24_000_000));
+
+    assert_eq!(model.divisor(), 13);
+    assert_eq!(model.mcr(), mcr);
+    assert_eq!(model.rx_len(), 0);
+}
+
+#[test]
+fn stuck_transmitter() {
+    let model = Model::new(Chip::U16550A);
+    let timed = Timed::new(&model, 1_843_200, 1_000_000);
+    let uart = Uart8250::from_access(&timed);
+    uart.init(1_843_200, 115200);
+    // Never shifts anything out
+    timed.done.set(u64::MAX);
+    model.write(offset::THR, 0);
+
+    assert_eq!(uart.measure_clock(&timed, 1, 16), Err(Error::NotDetected));
+    assert_eq!(nearest_common_clock(1_000_000, 20_000), None);
+}
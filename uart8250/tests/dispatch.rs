@@ -0,0 +1,131 @@
+use std::collections::VecDeque;
+
+use uart8250::access::RegisterAccess;
+use uart8250::dispatch::{InterruptHandler, MAX_INTERRUPT_CAUSES};
+use uart8250::line_break::RxEvent;
+use uart8250::model::{Chip, Model, ModemInputs};
+use uart8250::registers::offset;
+use uart8250::{Error, LineError, Uart8250, IER, MSR};
+
+/// Records everything it is called with, and sends from a queue
+#[derive(Default)]
+struct Recorder {
+    received: Vec<RxEvent>,
+    errors: Vec<LineError>,
+    modem: Vec<MSR>,
+    queue: VecDeque<u8>,
+}
+
+impl InterruptHandler for Recorder {
+    fn receive(&mut self, event: RxEvent) {
+        self.received.push(event);
+    }
+
+    fn line_error(&mut self, error: LineError) {
+        self.errors.push(error);
+    }
+
+    fn transmit(&mut self) -> Option<u8> {
+        self.queue.pop_front()
+    }
+
+    fn modem_status(&mut self, msr: MSR) {
+        self.modem.push(msr);
+    }
+}
+
+#[test]
+fn services_every_cause() {
+    let model = Model::new(Chip::U16550A);
+    let uart = Uart8250::from_access(&model);
+    uart.init(1_843_200, 115200);
+    uart.set_ier(IER::RDAI | IER::RLSI | IER::MSI);
+    let mut recorder = Recorder::default();
+
+    model.receive(b'a');
+    model.receive_parity_error(b'b');
+    model.set_modem_inputs(ModemInputs {
+        cts: true,
+        ..ModemInputs::default()
+    });
+    assert!(model.interrupt());
+
+    assert!(uart.dispatch_interrupts(&mut recorder).is_ok());
+    assert!(!model.interrupt());
+    assert_eq!(
+        recorder.received,
+        [
+            RxEvent::Data(b'a'),
+            RxEvent::Error {
+                byte: b'b',
+                error: LineError::Parity
+            }
+        ]
+    );
+    assert_eq!(recorder.modem, [MSR::CTS | MSR::DCTS]);
+    assert_eq!(uart.dispatch_interrupts(&mut recorder), Ok(0));
+}
+
+#[test]
+fn fills_the_transmit_fifo() {
+    let model = Model::new(Chip::U16550A);
+    model.hold_transmitter(true);
+    let uart = Uart8250::from_access(&model);
+    uart.init(1_843_200, 115200);
+    let mut recorder = Recorder {
+        queue: (0..20).collect(),
+        ..Recorder::default()
+    };
+
+    uart.set_ier(IER::THREI);
+    assert_eq!(uart.dispatch_interrupts(&mut recorder), Ok(1));
+    assert_eq!(model.tx_len(), 16);
+
+    while model.shift_out().is_some() {}
+    // A received character comes first, THR is refilled on the same pass
+    uart.set_ier(IER::THREI | IER::RDAI);
+    model.receive(b'x');
+    assert_eq!(uart.dispatch_interrupts(&mut recorder), Ok(1));
+    assert_eq!(recorder.received, [RxEvent::Data(b'x')]);
+    assert_eq!(model.tx_len(), 4);
+
+    while model.shift_out().is_some() {}
+    assert_eq!(uart.dispatch_interrupts(&mut recorder), Ok(1));
+    assert!(!model.interrupt());
+    let sent: Vec<u8> = std::iter::from_fn(|| model.transmitted()).collect();
+    assert_eq!(sent, (0..20).collect::<Vec<u8>>());
+}
+
+/// IIR always reports a modem status interrupt
+struct Stuck<'a>(&'a Model);
+
+impl RegisterAccess for Stuck<'_> {
+    fn read(&self, offset: usize) -> u8 {
+        match offset {
+            offset::IIR => 0b0000_0000,
+            _ => self.0.read(offset),
+        }
+    }
+
+    fn write(&self, offset: usize, value: u8) {
+        self.0.write(offset, value)
+    }
+}
+
+#[test]
+fn bounded_by_max_causes() {
+    let model = Model::new(Chip::U16550A);
+    let stuck = Stuck(&model);
+    let uart = Uart8250::from_access(&stuck);
+    uart.init(1_843_200, 115200);
+    let mut recorder = Recorder::default();
+
+    assert_eq!(
+        uart.dispatch_interrupts(&mut recorder),
+        Err(Error::UnexpectedRegisterValue {
+            offset: offset::IIR,
+            value: 0,
+        })
+    );
+    assert_eq!(recorder.modem.len(), MAX_INTERRUPT_CAUSES);
+}
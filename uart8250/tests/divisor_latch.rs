@@ -0,0 +1,46 @@
+use std::panic::{catch_unwind, AssertUnwindSafe};
+
+use uart8250::model::{Chip, Model};
+use uart8250::{Uart8250, IER};
+
+#[test]
+fn interrupts_are_masked_while_open() {
+    let model = Model::new(Chip::U16550A);
+    let uart = Uart8250::from_access(&model);
+    uart.init(1_843_200, 115200);
+    uart.set_ier(IER::RDAI | IER::THREI);
+    let lcr = model.lcr();
+
+    uart.with_divisor_latch(|latch| {
+        assert_eq!(model.ier(), IER::empty());
+        assert_eq!(model.lcr(), lcr | 0x80);
+        latch.write_divisor(0x0102);
+        assert_eq!(latch.divisor(), 0x0102);
+    });
+    assert_eq!(model.divisor(), 0x0102);
+    assert_eq!(model.lcr(), lcr);
+    assert_eq!(model.ier(), IER::RDAI | IER::THREI);
+}
+
+#[test]
+fn restored_after_panic() {
+    let model = Model::new(Chip::U16550A);
+    let uart = Uart8250::from_access(&model);
+    uart.init(1_843_200, 115200);
+    uart.set_ier(IER::RLSI);
+    uart.enable_divisor_latch_accessible();
+    let lcr = model.lcr();
+
+    let result = catch_unwind(AssertUnwindSafe(|| {
+        uart.with_divisor_latch(|latch| {
+            latch.write_divisor(6);
+            panic!("oops");
+        })
+    }));
+    assert!(result.is_err());
+    assert_eq!(model.divisor(), 6);
+    // DLAB was set before, so it still is, and IER was written with it clear
+    assert_eq!(model.lcr(), lcr);
+    uart.disable_divisor_latch_accessible();
+    assert_eq!(model.ier(), IER::RLSI);
+}
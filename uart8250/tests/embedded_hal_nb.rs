@@ -0,0 +1,53 @@
+use embedded_hal_nb::nb;
+use embedded_hal_nb::serial::{Read, Write};
+use uart8250::model::{Chip, Model};
+use uart8250::{LineError, Uart8250};
+
+#[test]
+fn read_reports_error_before_data() {
+    let model = Model::new(Chip::U16550A);
+    let mut uart = Uart8250::from_access(&model);
+    uart.init(1_843_200, 115200);
+
+    assert_eq!(uart.read(), Err(nb::Error::WouldBlock));
+    model.receive_parity_error(b'a');
+    assert_eq!(uart.read(), Err(nb::Error::Other(LineError::Parity)));
+    assert_eq!(uart.read(), Ok(b'a'));
+
+    model.receive_break();
+    assert_eq!(uart.read(), Err(nb::Error::Other(LineError::Break)));
+    assert_eq!(uart.read(), Ok(0));
+}
+
+#[test]
+fn write_blocks_on_full_transmitter() {
+    let model = Model::new(Chip::U16450);
+    let mut uart = Uart8250::from_access(&model);
+    uart.init(1_843_200, 115200);
+    model.hold_transmitter(true);
+
+    assert_eq!(uart.write(b'a'), Ok(()));
+    assert_eq!(uart.write(b'b'), Err(nb::Error::WouldBlock));
+    assert_eq!(uart.flush(), Err(nb::Error::WouldBlock));
+    model.shift_out();
+    assert_eq!(uart.flush(), Ok(()));
+    assert_eq!(model.transmitted(), Some(b'a'));
+}
+
+#[test]
+fn write_keeps_errors_for_read() {
+    let model = Model::new(Chip::U16550A);
+    let mut uart = Uart8250::from_access(&model);
+    uart.init(1_843_200, 115200);
+
+    model.receive_parity_error(b'a');
+    assert_eq!(uart.write(b'x'), Ok(()));
+    assert_eq!(uart.read(), Err(nb::Error::Other(LineError::Parity)));
+    assert_eq!(uart.read(), Ok(b'a'));
+
+    model.receive_framing_error(b'b');
+    assert_eq!(uart.flush(), Ok(()));
+    assert_eq!(uart.read(), Err(nb::Error::Other(LineError::Framing)));
+    assert_eq!(uart.read(), Ok(b'b'));
+    assert!(uart.line_errors().is_empty());
+}
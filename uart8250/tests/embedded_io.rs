@@ -0,0 +1,76 @@
+use embedded_io::{Read, ReadReady, Write, WriteReady};
+use uart8250::model::{Chip, Model};
+use uart8250::{LineError, Uart8250, LSR};
+
+#[test]
+fn read_returns_available_bytes() {
+    let model = Model::new(Chip::U16550A);
+    let mut uart = Uart8250::from_access(&model);
+    uart.init(1_843_200, 115200);
+
+    assert_eq!(uart.read_ready(), Ok(false));
+    model.receive_all(b"abc");
+    assert_eq!(uart.read_ready(), Ok(true));
+    let mut buf = [0; 8];
+    assert_eq!(uart.read(&mut buf), Ok(3));
+    assert_eq!(&buf[..3], b"abc");
+
+    model.receive_framing_error(b'd');
+    assert_eq!(uart.read(&mut buf), Err(LineError::Framing));
+    assert_eq!(uart.read(&mut buf), Ok(1));
+}
+
+#[test]
+fn write_and_flush() {
+    let model = Model::new(Chip::U16550A);
+    let mut uart = Uart8250::from_access(&model);
+    uart.init(1_843_200, 115200);
+
+    assert_eq!(uart.write_ready(), Ok(true));
+    uart.write_all(b"hello").unwrap();
+    uart.flush().unwrap();
+    for &byte in b"hello" {
+        assert_eq!(model.transmitted(), Some(byte));
+    }
+}
+
+#[test]
+fn line_error_after_bytes_is_returned_next() {
+    let model = Model::new(Chip::U16550A);
+    let mut uart = Uart8250::from_access(&model);
+    uart.init(1_843_200, 115200);
+
+    model.receive_all(b"ab");
+    model.receive_parity_error(b'c');
+    let mut buf = [0; 8];
+    assert_eq!(uart.read(&mut buf), Ok(2));
+    assert_eq!(&buf[..2], b"ab");
+    assert_eq!(uart.read(&mut buf), Err(LineError::Parity));
+    assert_eq!(uart.read(&mut buf), Ok(1));
+    assert_eq!(buf[0], b'c');
+    // It was returned, so it isn't kept as well
+    assert_eq!(uart.take_line_errors(), LSR::empty());
+}
+
+#[test]
+fn write_keeps_errors_for_read() {
+    let model = Model::new(Chip::U16550A);
+    let mut uart = Uart8250::from_access(&model);
+    uart.init(1_843_200, 115200);
+    let mut buf = [0; 8];
+
+    model.receive_parity_error(b'a');
+    assert_eq!(uart.write(b"x"), Ok(1));
+    assert_eq!(uart.read(&mut buf), Err(LineError::Parity));
+    assert_eq!(uart.read(&mut buf), Ok(1));
+
+    model.receive_framing_error(b'b');
+    uart.flush().unwrap();
+    assert_eq!(uart.read_ready(), Err(LineError::Framing));
+    assert_eq!(uart.read(&mut buf), Ok(1));
+
+    model.receive_break();
+    assert_eq!(uart.write_ready(), Ok(true));
+    assert_eq!(uart.read(&mut buf), Err(LineError::Break));
+    assert!(uart.line_errors().is_empty());
+}
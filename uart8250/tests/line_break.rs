@@ -0,0 +1,74 @@
+use core::time::Duration;
+
+use uart8250::line_break::{Delay, RxEvent};
+use uart8250::model::{Chip, Model};
+use uart8250::{LineError, Uart8250};
+
+/// Records the delays, and whether a break was being sent during them
+struct Recorder<'a> {
+    model: &'a Model,
+    delays: Vec<(u32, bool)>,
+}
+
+impl Delay for Recorder<'_> {
+    fn delay_us(&mut self, us: u32) {
+        self.delays.push((us, self.model.is_sending_break()));
+    }
+}
+
+#[test]
+fn send_break_for_duration() {
+    let model = Model::new(Chip::U16550A);
+    let uart = Uart8250::from_access(&model);
+    uart.init(1_843_200, 115200);
+    let mut recorder = Recorder {
+        model: &model,
+        delays: Vec::new(),
+    };
+
+    uart.send_break(Duration::from_millis(250), &mut recorder);
+    assert_eq!(recorder.delays, [(250_000, true)]);
+    assert!(!uart.is_sending_break());
+    assert_eq!(model.lcr(), 0b0000_0011);
+
+    uart.start_break();
+    assert!(model.is_sending_break());
+    uart.stop_break();
+    assert!(!model.is_sending_break());
+}
+
+#[test]
+fn break_is_a_receive_event() {
+    let model = Model::new(Chip::U16550A);
+    let uart = Uart8250::from_access(&model);
+    uart.init(1_843_200, 115200);
+
+    model.receive(b'a');
+    model.receive_break();
+    model.receive_parity_error(b'b');
+    model.receive(b'c');
+
+    assert_eq!(uart.read_event(), Some(RxEvent::Data(b'a')));
+    assert_eq!(uart.read_event(), Some(RxEvent::Break { byte: 0 }));
+    assert_eq!(
+        uart.read_event(),
+        Some(RxEvent::Error {
+            byte: b'b',
+            error: LineError::Parity
+        })
+    );
+    assert_eq!(uart.read_event(), Some(RxEvent::Data(b'c')));
+    assert_eq!(uart.read_event(), None);
+}
+
+#[test]
+fn break_in_loopback() {
+    let model = Model::new(Chip::U16450);
+    let uart = Uart8250::from_access(&model);
+    uart.init(1_843_200, 115200);
+    uart.enable_loopback_mode();
+
+    uart.start_break();
+    uart.stop_break();
+    assert_eq!(uart.read_event(), Some(RxEvent::Break { byte: 0 }));
+}
@@ -0,0 +1,441 @@
+use uart8250::access::RegisterAccess;
+use uart8250::model::{Chip, Model, ModemInputs};
+use uart8250::registers::offset;
+use uart8250::{
+    ChipFifoInfo, Error, FifoConfig, FifoMode, InterruptType, LineConfig, LineError, Parity,
+    TriggerLevel, Uart8250, FCR, IER, LSR, MCR, MSR,
+};
+
+#[test]
+fn init_programs_divisor_and_line() {
+    let model = Model::new(Chip::U16550A);
+    let uart = Uart8250::from_access(&model);
+    uart.init(1_843_200, 9600);
+
+    assert_eq!(model.divisor(), 12);
+    assert_eq!(model.lcr(), 0b0000_0011);
+    assert_eq!(model.ier(), IER::RDAI);
+    assert_eq!(uart.read_fifo_status(), ChipFifoInfo::Enabled);
+}
+
+#[test]
+fn receive_and_transmit() {
+    let model = Model::new(Chip::U16550A);
+    let uart = Uart8250::from_access(&model);
+    uart.init(1_843_200, 115200);
+
+    assert_eq!(uart.read_byte(), None);
+    model.receive_all(b"hi");
+    assert_eq!(uart.read_byte(), Some(b'h'));
+    assert_eq!(uart.read_byte(), Some(b'i'));
+    assert_eq!(uart.read_byte(), None);
+
+    uart.write_byte(b'x');
+    assert_eq!(model.transmitted(), Some(b'x'));
+    assert_eq!(model.transmitted(), None);
+}
+
+#[test]
+fn line_errors_clear_on_lsr_read() {
+    let model = Model::new(Chip::U16550A);
+    let uart = Uart8250::from_access(&model);
+    uart.init(1_843_200, 115200);
+
+    model.receive_parity_error(b'a');
+    model.receive_framing_error(b'b');
+    let lsr = uart.lsr();
+    assert!(lsr.contains(LSR::PE | LSR::RFE));
+    assert!(!lsr.contains(LSR::FE));
+    assert!(!uart.lsr().contains(LSR::PE));
+
+    // The framing error shows up once its character reaches the top of the FIFO
+    assert_eq!(uart.read_byte(), Some(b'a'));
+    assert!(uart.lsr().contains(LSR::FE));
+    assert_eq!(uart.read_byte(), Some(b'b'));
+    assert!(!uart.lsr().contains(LSR::RFE));
+}
+
+#[test]
+fn line_errors_are_sticky() {
+    let model = Model::new(Chip::U16450);
+    let uart = Uart8250::from_access(&model);
+    uart.init(1_843_200, 115200);
+
+    model.receive_parity_error(b'a');
+    assert!(uart.is_data_ready());
+    // Lost by the first read, but kept by the driver
+    assert!(!uart.is_parity_error());
+    assert_eq!(uart.line_errors(), LSR::PE);
+
+    model.receive_framing_error(b'b');
+    let status = uart.line_status();
+    assert!(status.is_data_ready() && status.is_framing_error() && status.is_overrun_error());
+    assert!(!status.is_parity_error());
+    assert_eq!(status.error(), Some(LineError::Overrun));
+
+    assert_eq!(uart.take_line_errors(), LSR::PE | LSR::FE | LSR::OE);
+    assert_eq!(uart.take_line_errors(), LSR::empty());
+}
+
+#[test]
+fn interrupt_id_is_one_read() {
+    let model = Model::new(Chip::U16550A);
+    let uart = Uart8250::from_access(&model);
+    uart.init(1_843_200, 115200);
+    uart.set_ier(IER::THREI);
+
+    let id = uart.interrupt_id();
+    assert!(id.is_pending());
+    assert_eq!(
+        id.interrupt_type(),
+        Some(InterruptType::TransmitterHoldingRegisterEmpty)
+    );
+    assert_eq!(id.fifo_status(), ChipFifoInfo::Enabled);
+    assert!(!id.is_64byte_fifo_enabled());
+
+    // The read cleared THRE
+    assert!(!uart.interrupt_id().is_pending());
+}
+
+#[test]
+fn overrun_keeps_fifo() {
+    let model = Model::new(Chip::U16550A);
+    let uart = Uart8250::from_access(&model);
+    uart.init(1_843_200, 115200);
+
+    for byte in 0..17 {
+        model.receive(byte);
+    }
+    assert_eq!(model.rx_len(), 16);
+    assert!(uart.lsr().contains(LSR::OE));
+    assert_eq!(uart.read_byte(), Some(0));
+}
+
+#[test]
+fn overrun_without_fifo_overwrites() {
+    let model = Model::new(Chip::U16450);
+    let uart = Uart8250::from_access(&model);
+    uart.init(1_843_200, 115200);
+
+    model.receive(1);
+    model.receive(2);
+    assert!(uart.is_overrun_error());
+    assert_eq!(uart.read_byte(), Some(2));
+    assert_eq!(uart.read_fifo_status(), ChipFifoInfo::NoFifo);
+}
+
+#[test]
+fn break_loads_nul() {
+    let model = Model::new(Chip::U16550A);
+    let uart = Uart8250::from_access(&model);
+    uart.init(1_843_200, 115200);
+
+    model.receive_break();
+    assert!(uart.lsr().contains(LSR::BI | LSR::DR));
+    assert_eq!(uart.read_byte(), Some(0));
+}
+
+#[test]
+fn interrupt_priority() {
+    let model = Model::new(Chip::U16550A);
+    let uart = Uart8250::from_access(&model);
+    uart.init(1_843_200, 115200);
+    uart.set_ier(IER::all());
+
+    // THRE is pending as soon as THREI is enabled with THR empty
+    assert!(model.interrupt());
+    model.set_modem_inputs(ModemInputs {
+        cts: true,
+        ..ModemInputs::default()
+    });
+    model.receive_parity_error(b'a');
+
+    assert_eq!(
+        uart.read_interrupt_type(),
+        Some(InterruptType::ReceiverLineStatus)
+    );
+    uart.read_lsr();
+    assert_eq!(
+        uart.read_interrupt_type(),
+        Some(InterruptType::ReceivedDataAvailable)
+    );
+    uart.read_rbr();
+    assert_eq!(
+        uart.read_interrupt_type(),
+        Some(InterruptType::TransmitterHoldingRegisterEmpty)
+    );
+    // Reading IIR cleared THRE
+    assert_eq!(uart.read_interrupt_type(), Some(InterruptType::ModemStatus));
+    assert!(uart.msr().contains(MSR::DCTS | MSR::CTS));
+    assert_eq!(uart.read_interrupt_type(), None);
+    assert!(!model.interrupt());
+}
+
+#[test]
+fn trigger_level_and_timeout() {
+    let model = Model::new(Chip::U16550A);
+    let uart = Uart8250::from_access(&model);
+    uart.init(1_843_200, 115200);
+    // 4 byte trigger level
+    uart.write_fcr(0b0100_0001);
+
+    model.receive_all(b"abc");
+    assert_eq!(uart.read_interrupt_type(), None);
+    model.idle();
+    assert_eq!(uart.read_interrupt_type(), Some(InterruptType::Timeout));
+    uart.read_rbr();
+    assert_eq!(uart.read_interrupt_type(), None);
+    model.receive_all(b"de");
+    assert_eq!(
+        uart.read_interrupt_type(),
+        Some(InterruptType::ReceivedDataAvailable)
+    );
+}
+
+#[test]
+fn fifo_64byte_needs_dlab() {
+    let model = Model::new(Chip::U16750);
+    let uart = Uart8250::from_access(&model);
+    uart.init(1_843_200, 115200);
+
+    uart.write_fcr(0b0010_0001);
+    assert!(!uart.is_64byte_fifo_enabled());
+    uart.enable_divisor_latch_accessible();
+    uart.write_fcr(0b0010_0001);
+    uart.disable_divisor_latch_accessible();
+    assert!(uart.is_64byte_fifo_enabled());
+
+    for byte in 0..64 {
+        model.receive(byte);
+    }
+    assert!(!uart.is_overrun_error());
+}
+
+#[test]
+fn fifo_config_is_kept() {
+    let model = Model::new(Chip::U16750);
+    let uart = Uart8250::from_access(&model);
+    assert_eq!(uart.fcr(), FCR::empty());
+    uart.init(1_843_200, 115200);
+    assert_eq!(uart.fifo_config().rx_trigger, TriggerLevel::Bytes1);
+
+    let config = FifoConfig {
+        fifo_64byte: true,
+        rx_trigger: TriggerLevel::Bytes4,
+        ..FifoConfig::new()
+    };
+    uart.set_fifo_config(&config);
+    assert_eq!(model.lcr(), 0b0000_0011);
+    assert!(uart.is_64byte_fifo_enabled());
+    assert_eq!(
+        uart.fcr(),
+        FCR::ENABLE | FCR::FIFO_64BYTE | FCR::RX_TRIGGER_LSB
+    );
+    assert_eq!(
+        uart.fifo_config(),
+        FifoConfig {
+            clear_rx: false,
+            clear_tx: false,
+            ..config
+        }
+    );
+    assert_eq!(uart.fifo_config().rx_trigger_bytes(), 16);
+
+    for byte in 0..15 {
+        model.receive(byte);
+    }
+    assert_eq!(uart.read_interrupt_type(), None);
+    model.receive(15);
+    assert_eq!(
+        uart.read_interrupt_type(),
+        Some(InterruptType::ReceivedDataAvailable)
+    );
+}
+
+#[test]
+fn loopback_maps_modem_lines() {
+    let model = Model::new(Chip::U16550A);
+    let uart = Uart8250::from_access(&model);
+    uart.init(1_843_200, 115200);
+
+    // Loopback, OUT2, OUT1, RTS, DTR
+    uart.write_mcr(0b0001_1111);
+    let msr = uart.msr();
+    assert!(msr.contains(MSR::CTS | MSR::DSR | MSR::RI | MSR::CD));
+    assert!(msr.contains(MSR::DCTS | MSR::DDSR | MSR::DDCD));
+
+    uart.write_byte(0x5a);
+    assert_eq!(model.transmitted(), None);
+    assert_eq!(uart.read_byte(), Some(0x5a));
+
+    // RI falling edge
+    uart.write_mcr(0b0001_1011);
+    assert!(uart.msr().contains(MSR::TERI));
+}
+
+#[test]
+fn modem_control_lines_are_kept() {
+    let model = Model::new(Chip::U16750);
+    let uart = Uart8250::from_access(&model);
+    uart.init(1_843_200, 115200);
+
+    uart.enable_auxiliary_output_2();
+    uart.enable_request_to_send();
+    uart.toggle_data_terminal_ready();
+    assert_eq!(uart.mcr(), MCR::OUT2 | MCR::RTS | MCR::DTR);
+    uart.disable_request_to_send();
+    uart.enable_auto_flow_control();
+    assert!(uart.is_auto_flow_control_enabled());
+    assert!(!uart.is_request_to_send_enabled());
+    assert_eq!(model.mcr(), (MCR::OUT2 | MCR::DTR | MCR::AFE).bits());
+
+    uart.enable_loopback_mode();
+    uart.enable_auxiliary_output_1();
+    assert!(uart.msr().contains(MSR::CD | MSR::DSR | MSR::RI));
+    uart.set_mcr(MCR::empty());
+    assert!(!uart.is_loopback_mode_enabled());
+}
+
+#[test]
+fn interrupt_enables_are_kept() {
+    let model = Model::new(Chip::U16750);
+    let uart = Uart8250::from_access(&model);
+    uart.init(1_843_200, 115200);
+
+    uart.enable_modem_status_interrupt();
+    uart.toggle_transmitter_holding_register_empty_interrupt();
+    uart.disable_received_data_available_interrupt();
+    uart.enable_sleep_mode();
+    assert_eq!(uart.ier(), IER::MSI | IER::THREI | IER::SM);
+    assert_eq!(model.ier(), uart.ier());
+
+    // Changes are made to the copy, not to what IER reads back
+    model.write(offset::IER, 0);
+    assert_eq!(uart.modify_ier(|ier| ier - IER::SM), IER::MSI | IER::THREI);
+    assert_eq!(model.ier(), IER::MSI | IER::THREI);
+
+    model.write(offset::IER, IER::RDAI.bits());
+    uart.reload_ier();
+    assert_eq!(uart.ier(), IER::RDAI);
+}
+
+#[test]
+fn held_transmitter() {
+    let model = Model::new(Chip::U16550A);
+    let uart = Uart8250::from_access(&model);
+    uart.init(1_843_200, 115200);
+    model.hold_transmitter(true);
+
+    uart.write_byte(1);
+    uart.write_byte(2);
+    assert!(!uart.is_transmitter_holding_register_empty());
+    assert_eq!(model.shift_out(), Some(1));
+    assert_eq!(model.shift_out(), Some(2));
+    assert!(uart.is_transmitter_holding_register_empty());
+    assert_eq!(model.transmitted(), Some(1));
+}
+
+#[test]
+fn scratch_register() {
+    let model = Model::new(Chip::U8250);
+    let uart = Uart8250::from_access(&model);
+    uart.write_sr(0x55);
+    assert_ne!(uart.read_sr(), 0x55);
+
+    let model = Model::new(Chip::U16450);
+    let uart = Uart8250::from_access(&model);
+    uart.write_sr(0x55);
+    assert_eq!(uart.read_sr(), 0x55);
+}
+
+#[test]
+fn line_config_round_trip() {
+    let model = Model::new(Chip::U16550A);
+    let uart = Uart8250::from_access(&model);
+    let config = LineConfig {
+        baud_rate: 9600,
+        data_bits: 7,
+        parity: Parity::Even,
+        stop_bits: 2,
+        fifo: FifoMode::Disabled,
+        interrupts: IER::RDAI | IER::RLSI,
+    };
+    uart.apply_line_config(1_843_200, &config);
+
+    assert_eq!(model.divisor(), 12);
+    assert_eq!(model.lcr(), 0b0001_1110);
+    assert_eq!(model.ier(), IER::RDAI | IER::RLSI);
+    assert_eq!(uart.line_config(1_843_200), config);
+    assert_eq!(model.lcr(), 0b0001_1110);
+}
+
+#[test]
+fn line_config_keeps_thre_interrupt() {
+    let model = Model::new(Chip::U16550A);
+    let uart = Uart8250::from_access(&model);
+    uart.apply_line_config(
+        1_843_200,
+        &LineConfig {
+            interrupts: IER::THREI,
+            ..LineConfig::new(115200)
+        },
+    );
+
+    assert_eq!(uart.line_config(1_843_200).fifo, FifoMode::Enabled);
+    assert_eq!(
+        uart.read_interrupt_type(),
+        Some(InterruptType::TransmitterHoldingRegisterEmpty)
+    );
+}
+
+#[test]
+#[should_panic(expected = "invalid configuration")]
+fn invalid_line_config_panics() {
+    let model = Model::new(Chip::U16550A);
+    let uart = Uart8250::from_access(&model);
+    uart.apply_line_config(
+        1_843_200,
+        &LineConfig {
+            data_bits: 9,
+            ..LineConfig::new(115200)
+        },
+    );
+}
+
+#[test]
+fn word_length_can_be_reduced() {
+    let model = Model::new(Chip::U16550A);
+    let uart = Uart8250::from_access(&model);
+    uart.init(1_843_200, 115200);
+
+    uart.set_word_length(8);
+    uart.set_word_length(5);
+    assert_eq!(uart.get_word_length(), 5);
+    assert_eq!(model.lcr(), 0b0000_0000);
+}
+
+#[test]
+fn try_methods_return_errors() {
+    let model = Model::new(Chip::U16550A);
+    let uart = Uart8250::from_access(&model);
+    uart.init(1_843_200, 115200);
+
+    assert_eq!(uart.try_set_stop_bit(3), Err(Error::InvalidConfig));
+    assert_eq!(uart.try_set_word_length(4), Err(Error::InvalidConfig));
+    assert_eq!(
+        uart.try_apply_line_config(1_843_200, &LineConfig::new(230400)),
+        Err(Error::UnsupportedDivisor)
+    );
+    assert_eq!(model.divisor(), 1);
+
+    // Even parity select without parity enable
+    uart.write_lcr(0b0001_0011);
+    assert_eq!(
+        uart.try_get_parity(),
+        Err(Error::UnexpectedRegisterValue {
+            offset: offset::LCR,
+            value: 0b0001_0011
+        })
+    );
+    assert_eq!(uart.try_read_interrupt_type(), Ok(None));
+}
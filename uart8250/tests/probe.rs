@@ -0,0 +1,148 @@
+use std::cell::Cell;
+
+use uart8250::access::RegisterAccess;
+use uart8250::model::{Chip, Model};
+use uart8250::probe::{Capabilities, ChipType};
+use uart8250::registers::offset;
+use uart8250::{Error, Uart8250, IER, LSR};
+
+/// A UART which takes 160 register accesses per divisor to send a character, with a shift
+/// register behind the transmitter
+struct Paced<'a> {
+    model: &'a Model,
+    /// A character is in the shift register
+    busy: Cell<bool>,
+    /// Register accesses since it went in
+    progress: Cell<usize>,
+}
+
+impl<'a> Paced<'a> {
+    fn new(model: &'a Model) -> Self {
+        model.hold_transmitter(true);
+        Self {
+            model,
+            busy: Cell::new(false),
+            progress: Cell::new(0),
+        }
+    }
+
+    fn tick(&self) {
+        if self.busy.get() {
+            self.progress.set(self.progress.get() + 1);
+            if self.progress.get() >= 160 * self.model.divisor() as usize {
+                self.busy.set(false);
+            }
+        }
+        if !self.busy.get() && self.model.shift_out().is_some() {
+            self.busy.set(true);
+            self.progress.set(0);
+        }
+    }
+}
+
+impl RegisterAccess for Paced<'_> {
+    fn read(&self, offset: usize) -> u8 {
+        self.tick();
+        let value = self.model.read(offset);
+        if offset == offset::LSR && self.busy.get() {
+            value & !0b0100_0000
+        } else {
+            value
+        }
+    }
+
+    fn write(&self, offset: usize, value: u8) {
+        self.tick();
+        self.model.write(offset, value)
+    }
+}
+
+fn probe(chip: Chip) -> Capabilities {
+    let model = Model::new(chip);
+    let paced = Paced::new(&model);
+    let uart = Uart8250::from_access(&paced);
+    uart.init(1_843_200, 115200);
+    uart.probe().unwrap()
+}
+
+#[test]
+fn identifies_chips() {
+    let capabilities = probe(Chip::U8250);
+    assert_eq!(capabilities.chip, ChipType::U8250);
+    assert!(!capabilities.scratch && !capabilities.fifo && !capabilities.efr);
+    assert!(capabilities.loopback);
+    assert_eq!(
+        (capabilities.tx_fifo_depth, capabilities.rx_fifo_depth),
+        (1, 1)
+    );
+
+    let capabilities = probe(Chip::U16450);
+    assert_eq!(capabilities.chip, ChipType::U16450);
+    assert!(capabilities.scratch && !capabilities.fifo);
+    assert_eq!(
+        (capabilities.tx_fifo_depth, capabilities.rx_fifo_depth),
+        (1, 1)
+    );
+
+    let capabilities = probe(Chip::U16550A);
+    assert_eq!(capabilities.chip, ChipType::U16550A);
+    assert!(capabilities.scratch && capabilities.fifo && !capabilities.efr);
+    assert_eq!(
+        (capabilities.tx_fifo_depth, capabilities.rx_fifo_depth),
+        (16, 16)
+    );
+
+    let capabilities = probe(Chip::U16750);
+    assert_eq!(capabilities.chip, ChipType::U16750);
+    assert!(capabilities.fifo && !capabilities.efr);
+    assert_eq!(
+        (capabilities.tx_fifo_depth, capabilities.rx_fifo_depth),
+        (64, 64)
+    );
+    assert_eq!(
+        capabilities.chip.divisor_variant(),
+        uart8250::DivisorVariant::Standard
+    );
+}
+
+#[test]
+fn probe_restores_configuration() {
+    let model = Model::new(Chip::U16550A);
+    let paced = Paced::new(&model);
+    let uart = Uart8250::from_access(&paced);
+    uart.init(1_843_200, 9600);
+    uart.set_ier(IER::RDAI | IER::RLSI);
+    uart.write_mcr(0b0000_1011);
+    uart.write_sr(0x42);
+    let lcr = model.lcr();
+
+    uart.probe().unwrap();
+
+    assert_eq!(model.divisor(), 12);
+    assert_eq!(model.lcr(), lcr);
+    assert_eq!(model.ier(), IER::RDAI | IER::RLSI);
+    assert_eq!(model.mcr(), 0b0000_1011);
+    assert_eq!(model.fcr() & 0b0010_0001, 0b0000_0001);
+    assert_eq!(uart.read_sr(), 0x42);
+    assert_eq!(model.rx_len(), 0);
+    assert_eq!(model.transmitted(), None);
+    // The overruns caused to measure the FIFOs didn't happen on the line
+    assert_eq!(uart.take_line_errors(), LSR::empty());
+}
+
+/// Nothing there, the bus reads all ones
+struct Absent;
+
+impl RegisterAccess for Absent {
+    fn read(&self, _offset: usize) -> u8 {
+        0xff
+    }
+
+    fn write(&self, _offset: usize, _value: u8) {}
+}
+
+#[test]
+fn missing_uart() {
+    let uart = Uart8250::from_access(Absent);
+    assert_eq!(uart.probe(), Err(Error::NotDetected));
+}
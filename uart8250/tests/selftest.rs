@@ -0,0 +1,81 @@
+use uart8250::access::RegisterAccess;
+use uart8250::model::{Chip, Model};
+use uart8250::registers::offset;
+use uart8250::selftest::Failure;
+use uart8250::{FifoConfig, TriggerLevel, Uart8250, IER, LSR, MCR, MSR};
+
+#[test]
+fn passes_and_restores_configuration() {
+    for chip in [Chip::U8250, Chip::U16450, Chip::U16550A, Chip::U16750] {
+        let model = Model::new(chip);
+        let uart = Uart8250::from_access(&model);
+        uart.init(1_843_200, 9600);
+        uart.set_fifo_config(&FifoConfig {
+            rx_trigger: TriggerLevel::Bytes8,
+            ..FifoConfig::new()
+        });
+        uart.set_mcr(MCR::OUT2 | MCR::DTR);
+        let lcr = model.lcr();
+
+        let report = uart.self_test();
+        assert!(report.passed(), "{:?}: {:?}", chip, report);
+
+        assert_eq!(model.lcr(), lcr);
+        assert_eq!(model.ier(), IER::RDAI);
+        assert_eq!(model.mcr(), (MCR::OUT2 | MCR::DTR).bits());
+        assert_eq!(model.divisor(), 12);
+        assert_eq!(uart.fifo_config().rx_trigger, TriggerLevel::Bytes8);
+        assert_eq!(model.rx_len(), 0);
+        assert_eq!(model.transmitted(), None);
+        assert_eq!(uart.take_line_errors(), LSR::empty());
+    }
+}
+
+/// Bit 0 of RBR is stuck low, and CTS doesn't follow RTS in loopback
+struct Faulty<'a>(&'a Model);
+
+impl RegisterAccess for Faulty<'_> {
+    fn read(&self, offset: usize) -> u8 {
+        let value = self.0.read(offset);
+        match offset {
+            offset::RBR if self.0.lcr() & 0x80 == 0 => value & !1,
+            offset::MSR => value & !MSR::CTS.bits(),
+            _ => value,
+        }
+    }
+
+    fn write(&self, offset: usize, value: u8) {
+        self.0.write(offset, value)
+    }
+}
+
+#[test]
+fn reports_failures() {
+    let model = Model::new(Chip::U16550A);
+    let faulty = Faulty(&model);
+    let uart = Uart8250::from_access(&faulty);
+    uart.init(1_843_200, 115200);
+
+    let report = uart.self_test();
+    assert!(!report.passed());
+    assert_eq!(
+        report.data[0][0],
+        Err(Failure::Data {
+            sent: 0x1f,
+            received: Some(0x1e)
+        })
+    );
+    assert!(report.data.iter().flatten().all(|check| check.is_err()));
+    assert_eq!(
+        report.modem_lines[0],
+        Err(Failure::ModemLines {
+            mcr: MCR::LOOPBACK | MCR::RTS,
+            expected: MSR::CTS,
+            actual: MSR::empty(),
+        })
+    );
+    assert!(report.modem_lines[1..].iter().all(|check| check.is_ok()));
+    assert!(report.overrun.is_ok() && report.break_detect.is_ok());
+    assert!(report.interrupts.iter().all(|check| check.is_ok()));
+    assert_eq!(report.failures().count(), 21);
+}
@@ -0,0 +1,44 @@
+use uart8250::line_break::RxEvent;
+use uart8250::model::{Chip, Model};
+use uart8250::{LineError, Uart8250, IER};
+
+#[test]
+fn halves_own_their_interrupts() {
+    let model = Model::new(Chip::U16550A);
+    let mut uart = Uart8250::from_access(&model);
+    uart.init(1_843_200, 115200);
+    uart.set_ier(IER::MSI);
+
+    let (rx, tx) = uart.split();
+    rx.enable_interrupt();
+    tx.enable_interrupt();
+    assert_eq!(model.ier(), IER::MSI | IER::RDAI | IER::RLSI | IER::THREI);
+    rx.disable_interrupt();
+    assert_eq!(model.ier(), IER::MSI | IER::THREI);
+    tx.disable_interrupt();
+    assert_eq!(model.ier(), IER::MSI);
+}
+
+#[test]
+fn errors_read_by_tx_go_to_rx() {
+    let model = Model::new(Chip::U16550A);
+    let mut uart = Uart8250::from_access(&model);
+    uart.init(1_843_200, 115200);
+
+    let (rx, tx) = uart.split();
+    model.receive_parity_error(b'a');
+    model.receive(b'b');
+    assert!(tx.is_ready());
+    assert!(tx.is_idle());
+    assert_eq!(rx.read_byte(), Err(LineError::Parity));
+    assert_eq!(rx.read_byte(), Ok(Some(b'a')));
+
+    model.receive_break();
+    assert!(tx.is_ready());
+    assert_eq!(rx.read_event(), Some(RxEvent::Data(b'b')));
+    assert_eq!(rx.read_event(), Some(RxEvent::Break { byte: 0 }));
+    assert_eq!(rx.read_event(), None);
+
+    tx.write_byte(b'c');
+    assert_eq!(model.transmitted(), Some(b'c'));
+}
@@ -0,0 +1,36 @@
+use uart8250::model::{Chip, Model};
+use uart8250::typestate::{Configured, Uart};
+use uart8250::Uart8250;
+
+#[test]
+fn configure_then_change_divisor() {
+    let model = Model::new(Chip::U16550A);
+    let uart = Uart::new(Uart8250::from_access(&model)).configure(1_843_200, 115200);
+    assert_eq!(model.divisor(), 1);
+
+    let old = uart.with_divisor_latch(|latch| {
+        let old = latch.divisor();
+        latch.write_divisor(0x0180);
+        old
+    });
+    assert_eq!(old, 1);
+    assert_eq!(model.divisor(), 0x0180);
+    assert_eq!(model.lcr(), 0b0000_0011);
+
+    uart.write_byte(b'x');
+    assert_eq!(model.transmitted(), Some(b'x'));
+}
+
+#[test]
+fn assume_configured_clears_dlab() {
+    let model = Model::new(Chip::U16550A);
+    let raw = Uart8250::from_access(&model);
+    raw.init(1_843_200, 115200);
+    raw.enable_divisor_latch_accessible();
+
+    let uart = Uart::<_, Configured>::assume_configured(raw);
+    assert_eq!(model.lcr() & 0x80, 0);
+    model.receive(b'a');
+    assert_eq!(uart.read_byte(), Some(b'a'));
+    assert_eq!(uart.into_inner().read_byte(), None);
+}
@@ -17,5 +17,6 @@ extern crate bitflags;
 pub mod uart_16550;
 pub mod uart_lite;
 
-pub use uart_16550::MmioUartAxi16550;
+pub use uart8250::{Error, LineError};
+pub use uart_16550::{Axi16550Layout, MmioUartAxi16550, TypestateUartAxi16550};
 pub use uart_lite::{MmioUartAxiLite, MmioUartXpsLite};
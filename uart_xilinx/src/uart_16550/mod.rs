@@ -2,10 +2,19 @@
 # uart_16550
 
 This mod provide structures with many methods to operate AXI Uart 16550
+
+The AXI UART 16550 is a 16550 with 32-bit registers placed 4 bytes apart,
+so it is driven by [`uart8250`] with [`Axi16550Layout`].
 */
 
-#[macro_use]
-pub mod registers;
 pub mod uart;
 
-pub use uart::{InterruptType, MmioUartAxi16550, Parity};
+#[cfg(feature = "embedded-io-async")]
+pub use uart::AsyncUartAxi16550;
+#[cfg(feature = "buffered")]
+pub use uart::BufferedUartAxi16550;
+pub use uart::{Axi16550Layout, MmioUartAxi16550, TypestateUartAxi16550};
+pub use uart8250::{
+    BaudPlan, ChipFifoInfo, Error, FifoConfig, FifoMode, InterruptId, InterruptType, LineConfig,
+    LineStatus, Parity, TriggerLevel, FCR, IER, LSR, MCR, MSR,
+};
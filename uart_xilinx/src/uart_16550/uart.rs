@@ -1,581 +1,58 @@
-#[cfg(feature = "fmt")]
-use core::fmt;
+use uart8250::access::{Mmio, RegisterLayout, RegisterWidth};
+use uart8250::Uart8250;
 
-use super::registers::Registers;
-
-bitflags! {
-    /// Interrupt Enable Register (bitflags)
-    pub struct IER: u8 {
-        /// Enable Received Data Available Interrupt
-        const RDAI  = 0b0000_0001;
-        /// Enable Transmitter Holding Register Empty Interrupt
-        const THREI = 0b0000_0010;
-        /// Enable Receiver Line Status Interrupt
-        const RLSI  = 0b0000_0100;
-        /// Enable Modem Status Interrupt
-        const MSI   = 0b0000_1000;
-    }
-}
-
-bitflags! {
-    /// Line Status Register (bitflags)
-    pub struct LSR: u8 {
-        /// Data Ready
-        const DR   = 0b0000_0001;
-        /// Overrun Error
-        const OE   = 0b0000_0010;
-        /// Parity Error
-        const PE   = 0b0000_0100;
-        /// Framing Error
-        const FE   = 0b0000_1000;
-        /// Break Interrupt
-        const BI   = 0b0001_0000;
-        /// Transmitter Holding Register Empty
-        const THRE = 0b0010_0000;
-        /// Data Holding Regiters Empty
-        const DHRE = 0b0100_0000;
-        /// Error in Received FIFO
-        const RFE  = 0b1000_0000;
-    }
-}
-
-bitflags! {
-    /// Modem Status Register (bitflags)
-    pub struct MSR: u8 {
-        /// Delta Clear To Send
-        const DCTS = 0b0000_0001;
-        ///Delta Data Set Ready
-        const DDSR = 0b0000_0010;
-        ///Trailing Edge Ring Indicator
-        const TERI = 0b0000_0100;
-        ///Delta Data Carrier Detect
-        const DDCD = 0b0000_1000;
-        ///Clear To Send
-        const CTS  = 0b0001_0000;
-        ///Data Set Ready
-        const DSR  = 0b0010_0000;
-        ///Ring Indicator
-        const RI   = 0b0100_0000;
-        ///Carrier Detect
-        const CD   = 0b1000_0000;
-    }
-}
-
-#[derive(Debug, Clone, PartialEq)]
-pub enum InterruptType {
-    ModemStatus,
-    TransmitterHoldingRegisterEmpty,
-    ReceivedDataAvailable,
-    ReceiverLineStatus,
-    Timeout,
-    Reserved,
-}
-
-#[derive(Debug, Clone, PartialEq)]
-pub enum Parity {
-    No,
-    Odd,
-    Even,
-    Mark,
-    Space,
-}
-
-/// # MMIO version of AXI UART 16550
+/// # Register layout of AXI UART 16550
 ///
-/// **Noticed** This hasn't been tested.
-pub struct MmioUartAxi16550<'a> {
-    reg: &'a mut Registers,
-}
-
-impl<'a> MmioUartAxi16550<'a> {
-    /// New a uart
-    pub fn new(base_address: usize) -> Self {
-        Self {
-            reg: cast!(base_address),
-        }
-    }
-
-    /// A basic way to init the uart with interrupt enable
-    ///
-    /// Other way to init can be done by using other methods below
-    pub fn init(&self, clock: usize, baud_rate: usize) {
-        // Enable DLAB and Set divisor
-        self.set_divisor(clock, baud_rate);
-
-        // Disable DLAB and set word length 8 bits, no parity, 1 stop bit
-        self.write_lcr(3);
-        // Enable FIFO
-        self.write_fcr(1);
-        // No modem control
-        self.write_mcr(0);
-        // Enable received_data_available_interrupt
-        self.enable_received_data_available_interrupt();
-        // Enable transmitter_holding_register_empty_interrupt
-        // self.enable_transmitter_holding_register_empty_interrupt();
-    }
-
-    /// Set a new base_address
-    pub fn set_base_address(&mut self, base_address: usize) {
-        self.reg = cast!(base_address);
-    }
-
-    /// Read a byte from uart
-    ///
-    /// Return `None` when data is not ready (RBR\[0\] != 1)
-    pub fn read_byte(&self) -> Option<u8> {
-        if self.is_data_ready() {
-            Some(self.read_rbr() as u8)
-        } else {
-            None
-        }
-    }
-
-    /// Write a byte to uart
-    ///
-    /// Error are not concerned now **MAYBE TODO**
-    pub fn write_byte(&self, byte: u8) {
-        self.write_thr(byte as u32);
-    }
-
-    /// write THR (offset + 0x0)
-    ///
-    /// Write Transmitter Holding Buffer to send data
-    #[inline]
-    pub fn write_thr(&self, value: u32) {
-        unsafe { self.reg.rw[0].write(value) }
-    }
-
-    /// read RBR (offset + 0x0)
-    ///
-    /// Read Receiver Buffer to get data
-    #[inline]
-    pub fn read_rbr(&self) -> u32 {
-        self.reg.rw[0].read()
-    }
-
-    /// read DLL (offset + 0x0)
-    ///
-    /// get divisor latch low byte in the register
-    #[inline]
-    pub fn read_dll(&self) -> u32 {
-        self.reg.rw[0].read()
-    }
-
-    /// write DLL (offset + 0x0)
-    ///
-    /// set divisor latch low byte in the register
-    #[inline]
-    pub fn write_dll(&self, value: u32) {
-        unsafe { self.reg.rw[0].write(value) }
-    }
-
-    /// read DLH (offset + 0x4)
-    ///
-    /// get divisor latch high byte in the register
-    #[inline]
-    pub fn read_dlh(&self) -> u32 {
-        self.reg.rw[1].read()
-    }
-
-    /// write DLH (offset + 0x4)
-    ///
-    /// set divisor latch high byte in the register
-    #[inline]
-    pub fn write_dlh(&self, value: u32) {
-        unsafe { self.reg.rw[1].write(value) }
-    }
-
-    /// Set divisor latch according to clock and baud_rate, then set DLAB to false
-    #[inline]
-    pub fn set_divisor(&self, clock: usize, baud_rate: usize) {
-        self.enable_divisor_latch_accessible();
-        let divisor = clock / (16 * baud_rate);
-        self.write_dll((divisor & 0b1111_1111) as u32);
-        self.write_dlh(((divisor >> 8) & 0b1111_1111) as u32);
-        self.disable_divisor_latch_accessible();
-    }
-
-    /// Read IER (offset + 0x4)
-    ///
-    /// Read IER to get what interrupts are enabled
-    #[inline]
-    pub fn read_ier(&self) -> u32 {
-        self.reg.rw[1].read()
-    }
+/// | Base Address | DLAB | I/O Access | Abbrv. | Register Name                     |
+/// | ------------ | ---- | ---------- | ------ | --------------------------------- |
+/// | +0x0         | 0    | Write      | THR    | Transmitter Holding Buffer        |
+/// | +0x0         | 0    | Read       | RBR    | Receiver Buffer                   |
+/// | +0x0         | 1    | Read/Write | DLL    | Divisor Latch Low Byte            |
+/// | +0x4         | 0    | Read/Write | IER    | Interrupt Enable Register         |
+/// | +0x4         | 1    | Read/Write | DLH    | Divisor Latch High Byte           |
+/// | +0x8         | x    | Read       | IIR    | Interrupt Identification Register |
+/// | +0x8         | x    | Write      | FCR    | FIFO Control Register             |
+/// | +0x8         | 1    | Read       | FCR    | FIFO Control Register             |
+/// | +0xc         | x    | Read/Write | LCR    | Line Control Register             |
+/// | +0x10        | x    | Read/Write | MCR    | Modem Control Register            |
+/// | +0x14        | x    | Read       | LSR    | Line Status Register              |
+/// | +0x18        | x    | Read       | MSR    | Modem Status Register             |
+/// | +0x1c        | x    | Read/Write | SR     | Scratch Register                  |
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub struct Axi16550Layout;
 
-    /// Write IER (offset + 0x4)
-    ///
-    /// Write Interrupt Enable Register to turn on/off interrupts
+impl RegisterLayout for Axi16550Layout {
     #[inline]
-    pub fn write_ier(&self, value: u32) {
-        unsafe { self.reg.rw[1].write(value) }
+    fn reg_shift(&self) -> u32 {
+        2
     }
 
-    /// Get IER bitflags
     #[inline]
-    pub fn ier(&self) -> IER {
-        IER::from_bits_truncate(self.read_ier() as u8)
+    fn reg_io_width(&self) -> RegisterWidth {
+        RegisterWidth::U32
     }
 
-    /// Set IER via bitflags
     #[inline]
-    pub fn set_ier(&self, flag: IER) {
-        self.write_ier(flag.bits() as u32)
-    }
-
-    /// get whether modem status interrupt is enabled (IER\[3\])
-    pub fn is_modem_status_interrupt_enabled(&self) -> bool {
-        self.ier().contains(IER::MSI)
-    }
-
-    /// toggle modem status interrupt (IER\[3\])
-    pub fn toggle_modem_status_interrupt(&self) {
-        self.set_ier(self.ier() ^ IER::MSI)
-    }
-
-    /// enable modem status interrupt (IER\[3\])
-    pub fn enable_modem_status_interrupt(&self) {
-        self.set_ier(self.ier() | IER::MSI)
-    }
-
-    /// disable modem status interrupt (IER\[3\])
-    pub fn disable_modem_status_interrupt(&self) {
-        self.set_ier(self.ier() & !IER::MSI)
-    }
-
-    /// get whether receiver line status interrupt is enabled (IER\[2\])
-    pub fn is_receiver_line_status_interrupt_enabled(&self) -> bool {
-        self.ier().contains(IER::RLSI)
-    }
-
-    /// toggle receiver line status interrupt (IER\[2\])
-    pub fn toggle_receiver_line_status_interrupt(&self) {
-        self.set_ier(self.ier() ^ IER::RLSI)
-    }
-
-    /// enable receiver line status interrupt (IER\[2\])
-    pub fn enable_receiver_line_status_interrupt(&self) {
-        self.set_ier(self.ier() | IER::RLSI)
-    }
-
-    /// disable receiver line status interrupt (IER\[2\])
-    pub fn disable_receiver_line_status_interrupt(&self) {
-        self.set_ier(self.ier() & !IER::RLSI)
-    }
-
-    /// get whether transmitter holding register empty interrupt is enabled (IER\[1\])
-    pub fn is_transmitter_holding_register_empty_interrupt_enabled(&self) -> bool {
-        self.ier().contains(IER::THREI)
-    }
-
-    /// toggle transmitter holding register empty interrupt (IER\[1\])
-    pub fn toggle_transmitter_holding_register_empty_interrupt(&self) {
-        self.set_ier(self.ier() ^ IER::THREI)
-    }
-
-    /// enable transmitter holding register empty interrupt (IER\[1\])
-    pub fn enable_transmitter_holding_register_empty_interrupt(&self) {
-        self.set_ier(self.ier() | IER::THREI)
-    }
-
-    /// disable transmitter holding register empty interrupt (IER\[1\])
-    pub fn disable_transmitter_holding_register_empty_interrupt(&self) {
-        self.set_ier(self.ier() & !IER::THREI)
-    }
-
-    /// get whether received data available is enabled (IER\[0\])
-    pub fn is_received_data_available_interrupt_enabled(&self) -> bool {
-        self.ier().contains(IER::RDAI)
-    }
-
-    /// toggle received data available (IER\[0\])
-    pub fn toggle_received_data_available_interrupt(&self) {
-        self.set_ier(self.ier() ^ IER::RDAI)
-    }
-
-    /// enable received data available (IER\[0\])
-    pub fn enable_received_data_available_interrupt(&self) {
-        self.set_ier(self.ier() | IER::RDAI)
-    }
-
-    /// disable received data available (IER\[0\])
-    pub fn disable_received_data_available_interrupt(&self) {
-        self.set_ier(self.ier() & !IER::RDAI)
-    }
-
-    /// Read IIR (offset + 0x8)
-    #[inline]
-    pub fn read_iir(&self) -> u32 {
-        self.reg.rw[2].read()
-    }
-
-    /// Read IIR\[7:6\] to get whether FIFO is enabled
-    pub fn is_fifo_enabled(&self) -> bool {
-        self.reg.rw[2].read() & 0b1100_0000 != 0
-    }
-
-    /// Read IIR\[3:1\] to get interrupt type
-    pub fn read_interrupt_type(&self) -> Option<InterruptType> {
-        let irq = self.reg.rw[2].read() & 0b0000_1111;
-        if irq & 1 != 0 {
-            None
-        } else {
-            match irq {
-                0b0000 => Some(InterruptType::ModemStatus),
-                0b0010 => Some(InterruptType::TransmitterHoldingRegisterEmpty),
-                0b0100 => Some(InterruptType::ReceivedDataAvailable),
-                0b0110 => Some(InterruptType::ReceiverLineStatus),
-                0b1100 => Some(InterruptType::Timeout),
-                0b1000 | 0b1010 | 0b1110 => Some(InterruptType::Reserved),
-                _ => panic!("Can't reached"),
-            }
-        }
-    }
-
-    /// get whether interrupt is pending (IIR\[0\])
-    ///
-    /// # Safety
-    ///
-    /// read iir will reset THREI, so use read_interrupt_type may be better
-    pub unsafe fn is_interrupt_pending(&self) -> bool {
-        self.reg.rw[2].read() & 1 == 0
-    }
-
-    /// Read FCR (offset + 0x8)
-    ///
-    /// # Safety
-    ///
-    /// In Xilinx doc, FCR can be read when DLAB == 1.
-    /// Please make sure DLAB is 1 when using this method.
-    #[inline]
-    pub unsafe fn read_fcr(&self) -> u32 {
-        self.reg.rw[2].read()
-    }
-
-    /// Write FCR (offset + 0x8) to control FIFO buffers
-    #[inline]
-    pub fn write_fcr(&self, value: u32) {
-        unsafe { self.reg.rw[2].write(value) }
-    }
-
-    /// Read LCR (offset + 0xc)
-    ///
-    /// Read Line Control Register to get the data protocol and DLAB
-    #[inline]
-    pub fn read_lcr(&self) -> u32 {
-        self.reg.rw[3].read()
-    }
-
-    /// Write LCR (offset + 0xc)
-    ///
-    /// Write Line Control Register to set DLAB and the serial data protocol
-    #[inline]
-    pub fn write_lcr(&self, value: u32) {
-        unsafe { self.reg.rw[3].write(value) }
-    }
-
-    /// get whether DLAB is enabled
-    pub fn is_divisor_latch_accessible(&self) -> bool {
-        self.reg.rw[3].read() & 0b1000_0000 != 0
-    }
-
-    /// toggle DLAB
-    pub fn toggle_divisor_latch_accessible(&self) {
-        unsafe { self.reg.rw[3].modify(|v| v ^ 0b1000_0000) }
-    }
-
-    /// enable DLAB
-    #[inline]
-    pub fn enable_divisor_latch_accessible(&self) {
-        unsafe { self.reg.rw[3].modify(|v| v | 0b1000_0000) }
-    }
-
-    /// disable DLAB
-    #[inline]
-    pub fn disable_divisor_latch_accessible(&self) {
-        unsafe { self.reg.rw[3].modify(|v| v & !0b1000_0000) }
-    }
-
-    /// get parity of used data protocol
-    pub fn get_parity(&self) -> Parity {
-        match self.reg.rw[3].read() & 0b0011_1000 {
-            0b0000_0000 => Parity::No,
-            0b0000_1000 => Parity::Odd,
-            0b0001_1000 => Parity::Even,
-            0b0010_1000 => Parity::Mark,
-            0b0011_1000 => Parity::Space,
-            _ => panic!("Invalid Parity! Please check your uart"),
-        }
-    }
-
-    /// set parity
-    pub fn set_parity(&self, parity: Parity) {
-        match parity {
-            Parity::No => unsafe { self.reg.rw[3].modify(|v| (v & 0b1100_0111)) },
-            Parity::Odd => unsafe { self.reg.rw[3].modify(|v| (v & 0b1100_0111) | 0b0000_1000) },
-            Parity::Even => unsafe { self.reg.rw[3].modify(|v| (v & 0b1100_0111) | 0b0001_1000) },
-            Parity::Mark => unsafe { self.reg.rw[3].modify(|v| (v & 0b1100_0111) | 0b0010_1000) },
-            Parity::Space => unsafe { self.reg.rw[3].modify(|v| v | 0b0011_1000) },
-        }
-    }
-
-    /// get stop bit of used data protocol
-    ///
-    /// Simply return a u8 to indicate 1 or 1.5/2 bits
-    pub fn get_stop_bit(&self) -> u32 {
-        ((self.reg.rw[3].read() & 0b100) >> 2) + 1
-    }
-
-    /// set stop bit, only 1 and 2 can be used as `stop_bit`
-    pub fn set_stop_bit(&self, stop_bit: u32) {
-        match stop_bit {
-            1 => unsafe { self.reg.rw[3].modify(|v| v & 0b1111_1011) },
-            2 => unsafe { self.reg.rw[3].modify(|v| v | 0b0000_0100) },
-            _ => panic!("Invalid stop bit"),
-        }
-    }
-
-    /// get word length of used data protocol
-    pub fn get_word_length(&self) -> u32 {
-        (self.reg.rw[3].read() & 0b11) + 5
-    }
-
-    /// set word length, only 5..=8 can be used as `length`
-    pub fn set_word_length(&self, length: u32) {
-        if (5..=8).contains(&length) {
-            unsafe { self.reg.rw[3].modify(|v| v | (length - 5)) }
-        } else {
-            panic!("Invalid word length")
-        }
-    }
-
-    /// Read MCR (offset + 0x10)
-    ///
-    /// Read Modem Control Register to get how flow is controlled
-    #[inline]
-    pub fn read_mcr(&self) -> u32 {
-        self.reg.rw[4].read()
-    }
-
-    /// Write MCR (offset + 0x10)
-    ///
-    /// Write Modem Control Register to control flow
-    #[inline]
-    pub fn write_mcr(&self, value: u32) {
-        unsafe { self.reg.rw[4].write(value) }
-    }
-
-    /// Read LSR (offset + 0x14)
-    #[inline]
-    pub fn read_lsr(&self) -> u32 {
-        self.reg.ro[0].read()
-    }
-
-    /// Get LSR bitflags
-    #[inline]
-    pub fn lsr(&self) -> LSR {
-        LSR::from_bits_truncate(self.read_lsr() as u8)
-    }
-
-    /// get whether there is an error in received FIFO
-    pub fn is_received_fifo_error(&self) -> bool {
-        self.lsr().contains(LSR::RFE)
-    }
-
-    /// get whether data holding registers are empty
-    pub fn is_data_holding_registers_empty(&self) -> bool {
-        self.lsr().contains(LSR::DHRE)
-    }
-
-    /// get whether transmitter holding register is empty
-    pub fn is_transmitter_holding_register_empty(&self) -> bool {
-        self.lsr().contains(LSR::THRE)
-    }
-
-    pub fn is_break_interrupt(&self) -> bool {
-        self.lsr().contains(LSR::BI)
-    }
-
-    pub fn is_framing_error(&self) -> bool {
-        self.lsr().contains(LSR::FE)
-    }
-
-    pub fn is_parity_error(&self) -> bool {
-        self.lsr().contains(LSR::PE)
-    }
-
-    pub fn is_overrun_error(&self) -> bool {
-        self.lsr().contains(LSR::OE)
-    }
-
-    pub fn is_data_ready(&self) -> bool {
-        self.lsr().contains(LSR::DR)
-    }
-
-    /// Read MSR (offset + 0x18)
-    #[inline]
-    pub fn read_msr(&self) -> u32 {
-        self.reg.ro[1].read()
-    }
-
-    /// Get MSR bitflags
-    #[inline]
-    pub fn msr(&self) -> MSR {
-        MSR::from_bits_truncate(self.read_msr() as u8)
-    }
-
-    pub fn is_carrier_detect(&self) -> bool {
-        self.msr().contains(MSR::CD)
-    }
-
-    pub fn is_ring_indicator(&self) -> bool {
-        self.msr().contains(MSR::RI)
-    }
-
-    pub fn is_data_set_ready(&self) -> bool {
-        self.msr().contains(MSR::DSR)
-    }
-
-    pub fn is_clear_to_send(&self) -> bool {
-        self.msr().contains(MSR::CTS)
-    }
-
-    pub fn is_delta_data_carrier_detect(&self) -> bool {
-        self.msr().contains(MSR::DDCD)
-    }
-
-    pub fn is_trailing_edge_ring_indicator(&self) -> bool {
-        self.msr().contains(MSR::TERI)
-    }
-
-    pub fn is_delta_data_set_ready(&self) -> bool {
-        self.msr().contains(MSR::DDSR)
+    fn is_big_endian(&self) -> bool {
+        false
     }
+}
 
-    pub fn is_delta_clear_to_send(&self) -> bool {
-        self.msr().contains(MSR::DCTS)
-    }
+/// # MMIO version of AXI UART 16550
+///
+/// **Noticed** This hasn't been tested.
+pub type MmioUartAxi16550<'a> = Uart8250<Mmio<'a, Axi16550Layout>>;
 
-    #[inline]
-    pub fn read_sr(&self) -> u32 {
-        self.reg.scratch.read()
-    }
+/// # Interrupt driven async AXI UART 16550
+#[cfg(feature = "embedded-io-async")]
+pub type AsyncUartAxi16550<'a> = uart8250::asynch::AsyncUart8250<Mmio<'a, Axi16550Layout>>;
 
-    #[inline]
-    pub fn write_sr(&self, value: u32) {
-        unsafe { self.reg.scratch.write(value) }
-    }
-}
+/// # Interrupt driven AXI UART 16550 with `RX` and `TX` byte ring buffers
+#[cfg(feature = "buffered")]
+pub type BufferedUartAxi16550<'a, const RX: usize, const TX: usize> =
+    uart8250::buffered::BufferedUart8250<Mmio<'a, Axi16550Layout>, RX, TX>;
 
-/// ## fmt::Write
+/// # AXI UART 16550 with its state tracked in its type
 ///
-/// A simple implementation, may be changed in the future
-#[cfg(feature = "fmt")]
-impl<'a> fmt::Write for MmioUartAxi16550<'a> {
-    fn write_str(&mut self, s: &str) -> fmt::Result {
-        for c in s.as_bytes() {
-            self.write_thr((*c) as u32);
-        }
-        Ok(())
-    }
-}
+/// See [`uart8250::typestate`] for the states.
+pub type TypestateUartAxi16550<'a, S> = uart8250::typestate::Uart<Mmio<'a, Axi16550Layout>, S>;
@@ -0,0 +1,175 @@
+/*!
+# Async UART Lite
+
+[`AsyncUartLite`] implements `embedded_io_async::Read` and `Write` for XPS and AXI UART Lite.
+
+UART Lite raises its single interrupt when the Rx FIFO becomes non-empty or the Tx FIFO becomes empty.
+The interrupt handler only has to call [`AsyncUartLite::handle_interrupt`], which wakes the waiting tasks
+without touching any register. Every register access is made in a critical section, including those
+through [`AsyncUartLite::with_uart`].
+*/
+
+use core::cell::Cell;
+use core::future::poll_fn;
+use core::task::Poll;
+
+use critical_section::CriticalSection;
+use uart8250::asynch::WakerSlot;
+use uart8250::LineError;
+
+use super::uart::{Status, UartLite, LINE_ERRORS};
+
+/// # Interrupt driven async UART Lite
+///
+/// Meant to live in a `static` shared by the tasks and the interrupt handler.
+pub struct AsyncUartLite<U: UartLite> {
+    uart: U,
+    rx_waker: WakerSlot,
+    tx_waker: WakerSlot,
+    /// Status error bits read but not returned yet
+    rx_errors: Cell<u8>,
+}
+
+// Like `critical_section::Mutex`: the UART and `rx_errors` are only reached in critical sections,
+// so only one thread uses them at a time, which `Send` allows.
+unsafe impl<U: UartLite + Send> Sync for AsyncUartLite<U> {}
+
+impl<U: UartLite> AsyncUartLite<U> {
+    pub const fn new(uart: U) -> Self {
+        Self {
+            uart,
+            rx_waker: WakerSlot::new(),
+            tx_waker: WakerSlot::new(),
+            rx_errors: Cell::new(0),
+        }
+    }
+
+    /// Enables the UART interrupt
+    pub fn init(&self) {
+        self.with_uart(|uart| uart.enable_interrupt());
+    }
+
+    /// Calls `f` with the underlying UART in a critical section, returning what it returns
+    pub fn with_uart<R>(&self, f: impl FnOnce(&U) -> R) -> R {
+        critical_section::with(|_| f(&self.uart))
+    }
+
+    /// Services the UART interrupt by waking the waiting tasks
+    pub fn handle_interrupt(&self) {
+        self.rx_waker.wake();
+        self.tx_waker.wake();
+    }
+
+    /// Read the status register, keeping its error bits for the next read
+    fn status(&self, cs: CriticalSection<'_>) -> Status {
+        let status = self.uart.status();
+        self.keep_rx_errors(cs, status);
+        status
+    }
+
+    /// Keep the error bits of `status` for the next read
+    fn keep_rx_errors(&self, _cs: CriticalSection<'_>, status: Status) {
+        self.rx_errors
+            .set(self.rx_errors.get() | (status & LINE_ERRORS).bits());
+    }
+
+    /// Take the error bits kept by [`keep_rx_errors`](Self::keep_rx_errors)
+    fn take_rx_errors(&self, _cs: CriticalSection<'_>) -> Status {
+        Status::from_bits_truncate(self.rx_errors.replace(0))
+    }
+
+    /// Read the characters which are ready, without waiting
+    fn read_ready_bytes(&self, buf: &mut [u8]) -> Result<usize, LineError> {
+        critical_section::with(|cs| {
+            let mut count = 0;
+            while count < buf.len() {
+                let status = self.uart.status() | self.take_rx_errors(cs);
+                if let Some(error) = status.line_error() {
+                    if count == 0 {
+                        return Err(error);
+                    }
+                    self.keep_rx_errors(cs, status);
+                    break;
+                } else if status.contains(Status::RX_FIFO_VALID) {
+                    buf[count] = self.uart.read_rx_byte();
+                    count += 1;
+                } else {
+                    break;
+                }
+            }
+            Ok(count)
+        })
+    }
+
+    /// Wait for at least one character, then read as many as are ready
+    pub async fn read(&self, buf: &mut [u8]) -> Result<usize, LineError> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        poll_fn(|cx| {
+            self.rx_waker.register(cx.waker());
+            match self.read_ready_bytes(buf) {
+                Ok(0) => Poll::Pending,
+                result => Poll::Ready(result),
+            }
+        })
+        .await
+    }
+
+    /// Wait for room in the Tx FIFO, then fill it
+    pub async fn write(&self, buf: &[u8]) -> Result<usize, LineError> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        poll_fn(|cx| {
+            self.tx_waker.register(cx.waker());
+            let count = critical_section::with(|cs| {
+                let mut count = 0;
+                while count < buf.len() && !self.status(cs).contains(Status::TX_FIFO_FULL) {
+                    self.uart.write_tx_byte(buf[count]);
+                    count += 1;
+                }
+                count
+            });
+            if count > 0 {
+                Poll::Ready(Ok(count))
+            } else {
+                Poll::Pending
+            }
+        })
+        .await
+    }
+
+    /// Wait for the Tx FIFO to be empty
+    pub async fn flush(&self) -> Result<(), LineError> {
+        poll_fn(|cx| {
+            self.tx_waker.register(cx.waker());
+            if critical_section::with(|cs| self.status(cs).contains(Status::TX_FIFO_EMPTY)) {
+                Poll::Ready(Ok(()))
+            } else {
+                Poll::Pending
+            }
+        })
+        .await
+    }
+}
+
+impl<U: UartLite> embedded_io_async::ErrorType for &AsyncUartLite<U> {
+    type Error = LineError;
+}
+
+impl<U: UartLite> embedded_io_async::Read for &AsyncUartLite<U> {
+    async fn read(&mut self, buf: &mut [u8]) -> Result<usize, Self::Error> {
+        AsyncUartLite::read(self, buf).await
+    }
+}
+
+impl<U: UartLite> embedded_io_async::Write for &AsyncUartLite<U> {
+    async fn write(&mut self, buf: &[u8]) -> Result<usize, Self::Error> {
+        AsyncUartLite::write(self, buf).await
+    }
+
+    async fn flush(&mut self) -> Result<(), Self::Error> {
+        AsyncUartLite::flush(self).await
+    }
+}
@@ -6,6 +6,12 @@ This mod provide structures with many methods to operate XPS Uart Lite and AXI U
 
 #[macro_use]
 pub mod registers;
+#[cfg(feature = "embedded-io-async")]
+pub mod asynch;
+#[cfg(feature = "split")]
+pub mod split;
 pub mod uart;
 
-pub use uart::{MmioUartAxiLite, MmioUartXpsLite, Status};
+#[cfg(feature = "embedded-io-async")]
+pub use asynch::AsyncUartLite;
+pub use uart::{MmioUartAxiLite, MmioUartXpsLite, Status, UartLite};
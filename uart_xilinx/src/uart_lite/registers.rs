@@ -1,16 +1,20 @@
-use volatile_register::{RO, WO};
-
 macro_rules! cast {
     ($expr:expr) => {
-        unsafe { &mut *(($expr) as *mut super::registers::Registers) }
+        ($expr) as *mut super::registers::Registers
     };
 }
 
 /// # UART Registers
+///
+/// Only describes the layout, the registers are accessed through raw pointers with volatile reads and writes.
 #[repr(C)]
 pub struct Registers {
-    pub rx: RO<u32>,
-    pub tx: WO<u32>,
-    pub stat: RO<u32>,
-    pub ctrl: WO<u32>,
+    /// Rx FIFO (read only)
+    pub rx: u32,
+    /// Tx FIFO (write only)
+    pub tx: u32,
+    /// Status Register (read only)
+    pub stat: u32,
+    /// Control Register (write only)
+    pub ctrl: u32,
 }
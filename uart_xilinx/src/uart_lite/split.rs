@@ -0,0 +1,96 @@
+/*!
+# Receive and transmit halves of UART Lite
+
+`split` on [`MmioUartXpsLite`](super::MmioUartXpsLite) and
+[`MmioUartAxiLite`](super::MmioUartAxiLite) borrows the UART as an [`Rx`] and a [`Tx`], which can
+be handed to different tasks or interrupt handlers.
+
+[`Rx`] only reads the Rx FIFO and [`Tx`] only writes the Tx FIFO. Both read the status register,
+and reading it clears its error bits, so the error bits [`Tx`] reads are kept for [`Rx`]. UART Lite
+has a single interrupt enable shared by both directions, which neither half changes.
+
+Every register access of the halves is made in a critical section.
+*/
+
+use core::cell::Cell;
+
+use uart8250::LineError;
+
+use super::uart::{Status, UartLite, LINE_ERRORS};
+
+/// # The receive half of UART Lite
+///
+/// See the [module documentation](self).
+pub struct Rx<'a, U: UartLite> {
+    uart: &'a U,
+    errors: &'a Cell<u8>,
+}
+
+// Every register access is made in a critical section, see the module documentation.
+unsafe impl<U: UartLite + Send> Send for Rx<'_, U> {}
+
+impl<'a, U: UartLite> Rx<'a, U> {
+    /// Reads the next received character, if there is one
+    ///
+    /// A line error is returned before the character it came with, which is returned by the next
+    /// read.
+    pub fn read_byte(&self) -> Result<Option<u8>, LineError> {
+        critical_section::with(|_| {
+            let status = self.uart.status() | Status::from_bits_truncate(self.errors.replace(0));
+            if let Some(error) = status.line_error() {
+                Err(error)
+            } else if status.contains(Status::RX_FIFO_VALID) {
+                Ok(Some(self.uart.read_rx_byte()))
+            } else {
+                Ok(None)
+            }
+        })
+    }
+
+    /// get whether a character is waiting
+    pub fn is_ready(&self) -> bool {
+        status(self.uart, self.errors).contains(Status::RX_FIFO_VALID)
+    }
+}
+
+/// # The transmit half of UART Lite
+///
+/// See the [module documentation](self).
+pub struct Tx<'a, U: UartLite> {
+    uart: &'a U,
+    errors: &'a Cell<u8>,
+}
+
+// Every register access is made in a critical section, see the module documentation.
+unsafe impl<U: UartLite + Send> Send for Tx<'_, U> {}
+
+impl<'a, U: UartLite> Tx<'a, U> {
+    /// Write a character to the Tx FIFO, without checking whether it is full
+    pub fn write_byte(&self, byte: u8) {
+        critical_section::with(|_| self.uart.write_tx_byte(byte))
+    }
+
+    /// get whether the Tx FIFO can take another character
+    pub fn is_ready(&self) -> bool {
+        !status(self.uart, self.errors).contains(Status::TX_FIFO_FULL)
+    }
+
+    /// get whether everything written has left the Tx FIFO
+    pub fn is_idle(&self) -> bool {
+        status(self.uart, self.errors).contains(Status::TX_FIFO_EMPTY)
+    }
+}
+
+/// Split `uart`, keeping the status error bits in `errors`
+pub(super) fn split<'a, U: UartLite>(uart: &'a U, errors: &'a Cell<u8>) -> (Rx<'a, U>, Tx<'a, U>) {
+    (Rx { uart, errors }, Tx { uart, errors })
+}
+
+/// Read the status register, keeping its error bits in `errors`
+fn status<U: UartLite>(uart: &U, errors: &Cell<u8>) -> Status {
+    critical_section::with(|_| {
+        let status = uart.status();
+        errors.set(errors.get() | (status & LINE_ERRORS).bits());
+        status
+    })
+}
@@ -1,7 +1,18 @@
+use core::cell::Cell;
 #[cfg(feature = "fmt")]
 use core::fmt;
+use core::marker::PhantomData;
+use core::ptr;
+
+#[cfg(feature = "embedded-hal-nb")]
+use embedded_hal_nb::nb;
+#[cfg(target_has_atomic = "8")]
+use uart8250::access::MmioDevice;
+use uart8250::LineError;
 
 use super::registers::Registers;
+#[cfg(feature = "split")]
+use super::split;
 
 bitflags! {
     /// Status Register Bit Definitions
@@ -17,6 +28,26 @@ bitflags! {
     }
 }
 
+/// The status bits cleared by reading the status register
+pub(super) const LINE_ERRORS: Status = Status::from_bits_truncate(
+    Status::OVERRUN_ERROR.bits() | Status::FRAME_ERROR.bits() | Status::PARITY_ERROR.bits(),
+);
+
+impl Status {
+    /// Get the most significant receive error, if any
+    pub fn line_error(&self) -> Option<LineError> {
+        if self.contains(Status::OVERRUN_ERROR) {
+            Some(LineError::Overrun)
+        } else if self.contains(Status::FRAME_ERROR) {
+            Some(LineError::Framing)
+        } else if self.contains(Status::PARITY_ERROR) {
+            Some(LineError::Parity)
+        } else {
+            None
+        }
+    }
+}
+
 bitflags! {
     /// Control Register Bit Definitions
     struct Control: u8 {
@@ -26,26 +57,89 @@ bitflags! {
     }
 }
 
+/// Run `f` in a critical section with feature `critical-section`, or just run it
+fn locked<R, F: FnOnce() -> R>(f: F) -> R {
+    #[cfg(feature = "critical-section")]
+    return critical_section::with(|_| f());
+    #[cfg(not(feature = "critical-section"))]
+    f()
+}
+
+/// Operations shared by XPS UART Lite and AXI UART Lite
+pub trait UartLite {
+    /// Get Uart Lite Status, leaving its error bits to the caller
+    ///
+    /// Unlike the `status` methods of the UARTs, the error bits aren't added to their
+    /// `line_errors`.
+    fn status(&self) -> Status;
+
+    /// Read a byte from the Rx FIFO, without checking whether it is valid
+    fn read_rx_byte(&self) -> u8;
+
+    /// Write a byte to the Tx FIFO, without checking whether it is full
+    fn write_tx_byte(&self, value: u8);
+
+    /// Enable the interrupt
+    fn enable_interrupt(&self);
+}
+
 /// # MMIO version of XPS UART Lite
 ///
 /// **Noticed** This hasn't been tested.
 pub struct MmioUartXpsLite<'a> {
-    reg: &'a mut Registers,
+    reg: *mut Registers,
+    /// Status error bits read but not returned yet, e.g. by the transmit half for the receive half
+    #[cfg(any(
+        feature = "split",
+        feature = "embedded-io",
+        feature = "embedded-hal-nb"
+    ))]
+    rx_errors: Cell<u8>,
+    /// Status error bits seen since the last `take_line_errors`
+    line_errors: Cell<u8>,
+    _marker: PhantomData<&'a mut Registers>,
 }
 
+// `new` requires exclusive access to the registers, so moving the UART to another thread is fine.
+unsafe impl<'a> Send for MmioUartXpsLite<'a> {}
+
 impl<'a> MmioUartXpsLite<'a> {
     /// New a uart
-    pub fn new(base_address: usize) -> Self {
+    ///
+    /// # Safety
+    ///
+    /// - `base_address` must be the base address of the UART registers, mapped as device memory for at least `'a`.
+    /// - Nothing else may access these registers while the returned value exists,
+    ///   including another UART created for the same address.
+    pub const unsafe fn new(base_address: usize) -> Self {
         Self {
             reg: cast!(base_address),
+            #[cfg(any(
+                feature = "split",
+                feature = "embedded-io",
+                feature = "embedded-hal-nb"
+            ))]
+            rx_errors: Cell::new(0),
+            line_errors: Cell::new(0),
+            _marker: PhantomData,
         }
     }
 
     /// Set a new base_address
-    pub fn set_base_address(&mut self, base_address: usize) {
+    ///
+    /// # Safety
+    ///
+    /// See [`MmioUartXpsLite::new`].
+    pub unsafe fn set_base_address(&mut self, base_address: usize) {
         self.reg = cast!(base_address);
     }
 
+    /// Splits the UART into its receive and transmit halves, see [`split`]
+    #[cfg(feature = "split")]
+    pub fn split(&mut self) -> (split::Rx<'_, Self>, split::Tx<'_, Self>) {
+        split::split(self, &self.rx_errors)
+    }
+
     /// Read a byte
     pub fn read_byte(&self) -> Option<u8> {
         if self.is_rx_fifo_valid() {
@@ -63,25 +157,81 @@ impl<'a> MmioUartXpsLite<'a> {
     /// Read Rx FIFO
     #[inline]
     pub fn read_rx(&self) -> u32 {
-        self.reg.rx.read()
+        unsafe { ptr::addr_of!((*self.reg).rx).read_volatile() }
     }
 
     /// Write Tx FIFO
     #[inline]
     pub fn write_tx(&self, value: u32) {
-        unsafe { self.reg.tx.write(value) }
+        unsafe { ptr::addr_of_mut!((*self.reg).tx).write_volatile(value) }
     }
 
     /// Read Uart Lite Status Register
     #[inline]
     pub fn read_stat(&self) -> u32 {
-        self.reg.stat.read()
+        unsafe { ptr::addr_of!((*self.reg).stat).read_volatile() }
     }
 
     /// Get Uart Lite Status
+    ///
+    /// Reading the status register clears its error bits, so they are also added to the errors
+    /// returned by [`take_line_errors`](Self::take_line_errors), in a critical section with
+    /// feature `critical-section`. Each `is_` method reads it again, so use this to check several
+    /// bits of one read.
     #[inline]
     pub fn status(&self) -> Status {
-        Status::from_bits_truncate(self.reg.stat.read().reverse_bits() as u8)
+        let status = self.raw_status();
+        if status.intersects(LINE_ERRORS) {
+            locked(|| {
+                self.line_errors
+                    .set(self.line_errors.get() | (status & LINE_ERRORS).bits())
+            });
+        }
+        status
+    }
+
+    /// Get the error bits of every status read since the last
+    /// [`take_line_errors`](Self::take_line_errors)
+    pub fn line_errors(&self) -> Status {
+        Status::from_bits_truncate(self.line_errors.get())
+    }
+
+    /// Get the error bits of every status read since the last call, and clear them
+    ///
+    /// Errors returned by a read, e.g. of `embedded_io::Read`, aren't collected.
+    pub fn take_line_errors(&self) -> Status {
+        Status::from_bits_truncate(locked(|| self.line_errors.replace(0)))
+    }
+
+    /// Get Uart Lite Status without adding its error bits to `line_errors`
+    #[inline]
+    fn raw_status(&self) -> Status {
+        Status::from_bits_truncate(self.read_stat().reverse_bits() as u8)
+    }
+
+    /// Read the status register for the transmitter, keeping its error bits for the next read
+    #[cfg(any(feature = "embedded-io", feature = "embedded-hal-nb"))]
+    fn tx_status(&self) -> Status {
+        locked(|| {
+            let status = self.raw_status();
+            self.keep_rx_errors(status);
+            status
+        })
+    }
+
+    /// Keep the error bits of `status` for the next read
+    #[cfg(any(feature = "embedded-io", feature = "embedded-hal-nb"))]
+    fn keep_rx_errors(&self, status: Status) {
+        locked(|| {
+            self.rx_errors
+                .set(self.rx_errors.get() | (status & LINE_ERRORS).bits())
+        });
+    }
+
+    /// Take the error bits kept by [`keep_rx_errors`](Self::keep_rx_errors)
+    #[cfg(any(feature = "embedded-io", feature = "embedded-hal-nb"))]
+    fn take_rx_errors(&self) -> Status {
+        Status::from_bits_truncate(locked(|| self.rx_errors.replace(0)))
     }
 
     pub fn is_rx_fifo_valid(&self) -> bool {
@@ -119,15 +269,16 @@ impl<'a> MmioUartXpsLite<'a> {
     /// Write Uart Lite Control Register
     #[inline]
     pub fn write_ctrl(&self, value: u32) {
-        unsafe { self.reg.ctrl.write(value) }
+        unsafe { ptr::addr_of_mut!((*self.reg).ctrl).write_volatile(value) }
     }
 
     pub fn enable_interrupt(&self) {
         self.write_ctrl((Control::ENABLE_INTERRUPT.bits() as u32).reverse_bits());
     }
 
+    /// Clears ENABLE_INTERRUPT, without resetting the FIFOs
     pub fn disable_interrupt(&self) {
-        self.write_ctrl((Control::ENABLE_INTERRUPT.bits() as u32).reverse_bits());
+        self.write_ctrl((Control::empty().bits() as u32).reverse_bits());
     }
 
     pub fn clear_rx_fifo(&self, enable_interrupt: bool) {
@@ -157,6 +308,34 @@ impl<'a> MmioUartXpsLite<'a> {
     }
 }
 
+#[cfg(target_has_atomic = "8")]
+impl MmioUartXpsLite<'static> {
+    /// Take the UART of `device`, which only succeeds the first time
+    pub fn take(device: &MmioDevice) -> Option<Self> {
+        let base_address = device.claim()?;
+        // SAFETY: `MmioDevice::new` promised the address is valid, and it is only handed out once.
+        Some(unsafe { Self::new(base_address) })
+    }
+}
+
+impl<'a> UartLite for MmioUartXpsLite<'a> {
+    fn status(&self) -> Status {
+        self.raw_status()
+    }
+
+    fn read_rx_byte(&self) -> u8 {
+        self.read_rx().reverse_bits() as u8
+    }
+
+    fn write_tx_byte(&self, value: u8) {
+        self.write_byte(value)
+    }
+
+    fn enable_interrupt(&self) {
+        MmioUartXpsLite::enable_interrupt(self)
+    }
+}
+
 /// ## fmt::Write
 ///
 /// A simple implementation, may be changed in the future
@@ -174,22 +353,59 @@ impl<'a> fmt::Write for MmioUartXpsLite<'a> {
 ///
 /// **Noticed** This hasn't been tested.
 pub struct MmioUartAxiLite<'a> {
-    reg: &'a mut Registers,
+    reg: *mut Registers,
+    /// Status error bits read but not returned yet, e.g. by the transmit half for the receive half
+    #[cfg(any(
+        feature = "split",
+        feature = "embedded-io",
+        feature = "embedded-hal-nb"
+    ))]
+    rx_errors: Cell<u8>,
+    /// Status error bits seen since the last `take_line_errors`
+    line_errors: Cell<u8>,
+    _marker: PhantomData<&'a mut Registers>,
 }
 
+// `new` requires exclusive access to the registers, so moving the UART to another thread is fine.
+unsafe impl<'a> Send for MmioUartAxiLite<'a> {}
+
 impl<'a> MmioUartAxiLite<'a> {
     /// New a uart
-    pub fn new(base_address: usize) -> Self {
+    ///
+    /// # Safety
+    ///
+    /// - `base_address` must be the base address of the UART registers, mapped as device memory for at least `'a`.
+    /// - Nothing else may access these registers while the returned value exists,
+    ///   including another UART created for the same address.
+    pub const unsafe fn new(base_address: usize) -> Self {
         Self {
             reg: cast!(base_address),
+            #[cfg(any(
+                feature = "split",
+                feature = "embedded-io",
+                feature = "embedded-hal-nb"
+            ))]
+            rx_errors: Cell::new(0),
+            line_errors: Cell::new(0),
+            _marker: PhantomData,
         }
     }
 
     /// Set a new base_address
-    pub fn set_base_address(&mut self, base_address: usize) {
+    ///
+    /// # Safety
+    ///
+    /// See [`MmioUartAxiLite::new`].
+    pub unsafe fn set_base_address(&mut self, base_address: usize) {
         self.reg = cast!(base_address);
     }
 
+    /// Splits the UART into its receive and transmit halves, see [`split`]
+    #[cfg(feature = "split")]
+    pub fn split(&mut self) -> (split::Rx<'_, Self>, split::Tx<'_, Self>) {
+        split::split(self, &self.rx_errors)
+    }
+
     /// Read a byte
     pub fn read_byte(&self) -> Option<u8> {
         if self.is_rx_fifo_valid() {
@@ -207,25 +423,81 @@ impl<'a> MmioUartAxiLite<'a> {
     /// Read Rx FIFO
     #[inline]
     pub fn read_rx(&self) -> u32 {
-        self.reg.rx.read()
+        unsafe { ptr::addr_of!((*self.reg).rx).read_volatile() }
     }
 
     /// Write Tx FIFO
     #[inline]
     pub fn write_tx(&self, value: u32) {
-        unsafe { self.reg.tx.write(value) }
+        unsafe { ptr::addr_of_mut!((*self.reg).tx).write_volatile(value) }
     }
 
     /// Read Uart Lite Status Register
     #[inline]
     pub fn read_stat(&self) -> u32 {
-        self.reg.stat.read()
+        unsafe { ptr::addr_of!((*self.reg).stat).read_volatile() }
     }
 
     /// Get Uart Lite Status
+    ///
+    /// Reading the status register clears its error bits, so they are also added to the errors
+    /// returned by [`take_line_errors`](Self::take_line_errors), in a critical section with
+    /// feature `critical-section`. Each `is_` method reads it again, so use this to check several
+    /// bits of one read.
     #[inline]
     pub fn status(&self) -> Status {
-        Status::from_bits_truncate(self.reg.stat.read() as u8)
+        let status = self.raw_status();
+        if status.intersects(LINE_ERRORS) {
+            locked(|| {
+                self.line_errors
+                    .set(self.line_errors.get() | (status & LINE_ERRORS).bits())
+            });
+        }
+        status
+    }
+
+    /// Get the error bits of every status read since the last
+    /// [`take_line_errors`](Self::take_line_errors)
+    pub fn line_errors(&self) -> Status {
+        Status::from_bits_truncate(self.line_errors.get())
+    }
+
+    /// Get the error bits of every status read since the last call, and clear them
+    ///
+    /// Errors returned by a read, e.g. of `embedded_io::Read`, aren't collected.
+    pub fn take_line_errors(&self) -> Status {
+        Status::from_bits_truncate(locked(|| self.line_errors.replace(0)))
+    }
+
+    /// Get Uart Lite Status without adding its error bits to `line_errors`
+    #[inline]
+    fn raw_status(&self) -> Status {
+        Status::from_bits_truncate(self.read_stat() as u8)
+    }
+
+    /// Read the status register for the transmitter, keeping its error bits for the next read
+    #[cfg(any(feature = "embedded-io", feature = "embedded-hal-nb"))]
+    fn tx_status(&self) -> Status {
+        locked(|| {
+            let status = self.raw_status();
+            self.keep_rx_errors(status);
+            status
+        })
+    }
+
+    /// Keep the error bits of `status` for the next read
+    #[cfg(any(feature = "embedded-io", feature = "embedded-hal-nb"))]
+    fn keep_rx_errors(&self, status: Status) {
+        locked(|| {
+            self.rx_errors
+                .set(self.rx_errors.get() | (status & LINE_ERRORS).bits())
+        });
+    }
+
+    /// Take the error bits kept by [`keep_rx_errors`](Self::keep_rx_errors)
+    #[cfg(any(feature = "embedded-io", feature = "embedded-hal-nb"))]
+    fn take_rx_errors(&self) -> Status {
+        Status::from_bits_truncate(locked(|| self.rx_errors.replace(0)))
     }
 
     pub fn is_rx_fifo_valid(&self) -> bool {
@@ -263,15 +535,16 @@ impl<'a> MmioUartAxiLite<'a> {
     /// Write Uart Lite Control Register
     #[inline]
     pub fn write_ctrl(&self, value: u32) {
-        unsafe { self.reg.ctrl.write(value) }
+        unsafe { ptr::addr_of_mut!((*self.reg).ctrl).write_volatile(value) }
     }
 
     pub fn enable_interrupt(&self) {
         self.write_ctrl(Control::ENABLE_INTERRUPT.bits() as u32);
     }
 
+    /// Clears ENABLE_INTERRUPT, without resetting the FIFOs
     pub fn disable_interrupt(&self) {
-        self.write_ctrl(Control::ENABLE_INTERRUPT.bits() as u32);
+        self.write_ctrl(Control::empty().bits() as u32);
     }
 
     pub fn clear_rx_fifo(&self, enable_interrupt: bool) {
@@ -300,6 +573,34 @@ impl<'a> MmioUartAxiLite<'a> {
     }
 }
 
+#[cfg(target_has_atomic = "8")]
+impl MmioUartAxiLite<'static> {
+    /// Take the UART of `device`, which only succeeds the first time
+    pub fn take(device: &MmioDevice) -> Option<Self> {
+        let base_address = device.claim()?;
+        // SAFETY: `MmioDevice::new` promised the address is valid, and it is only handed out once.
+        Some(unsafe { Self::new(base_address) })
+    }
+}
+
+impl<'a> UartLite for MmioUartAxiLite<'a> {
+    fn status(&self) -> Status {
+        self.raw_status()
+    }
+
+    fn read_rx_byte(&self) -> u8 {
+        self.read_rx() as u8
+    }
+
+    fn write_tx_byte(&self, value: u8) {
+        self.write_byte(value)
+    }
+
+    fn enable_interrupt(&self) {
+        MmioUartAxiLite::enable_interrupt(self)
+    }
+}
+
 /// ## fmt::Write
 ///
 /// A simple implementation, may be changed in the future
@@ -312,3 +613,259 @@ impl<'a> fmt::Write for MmioUartAxiLite<'a> {
         Ok(())
     }
 }
+
+#[cfg(feature = "embedded-hal-nb")]
+impl<'a> embedded_hal_nb::serial::ErrorType for MmioUartXpsLite<'a> {
+    type Error = LineError;
+}
+
+/// ## embedded_hal_nb::serial::Read
+///
+/// A line error is returned before the character it came with, which is returned by the next read.
+#[cfg(feature = "embedded-hal-nb")]
+impl<'a> embedded_hal_nb::serial::Read for MmioUartXpsLite<'a> {
+    fn read(&mut self) -> nb::Result<u8, Self::Error> {
+        let status = self.raw_status() | self.take_rx_errors();
+        if let Some(error) = status.line_error() {
+            Err(nb::Error::Other(error))
+        } else if status.contains(Status::RX_FIFO_VALID) {
+            Ok(self.read_rx().reverse_bits() as u8)
+        } else {
+            Err(nb::Error::WouldBlock)
+        }
+    }
+}
+
+/// ## embedded_hal_nb::serial::Write
+///
+/// Status error bits read while waiting are kept for the next read.
+#[cfg(feature = "embedded-hal-nb")]
+impl<'a> embedded_hal_nb::serial::Write for MmioUartXpsLite<'a> {
+    fn write(&mut self, word: u8) -> nb::Result<(), Self::Error> {
+        if self.tx_status().contains(Status::TX_FIFO_FULL) {
+            Err(nb::Error::WouldBlock)
+        } else {
+            self.write_byte(word);
+            Ok(())
+        }
+    }
+
+    fn flush(&mut self) -> nb::Result<(), Self::Error> {
+        if self.tx_status().contains(Status::TX_FIFO_EMPTY) {
+            Ok(())
+        } else {
+            Err(nb::Error::WouldBlock)
+        }
+    }
+}
+
+#[cfg(feature = "embedded-hal-nb")]
+impl<'a> embedded_hal_nb::serial::ErrorType for MmioUartAxiLite<'a> {
+    type Error = LineError;
+}
+
+/// ## embedded_hal_nb::serial::Read
+///
+/// A line error is returned before the character it came with, which is returned by the next read.
+#[cfg(feature = "embedded-hal-nb")]
+impl<'a> embedded_hal_nb::serial::Read for MmioUartAxiLite<'a> {
+    fn read(&mut self) -> nb::Result<u8, Self::Error> {
+        let status = self.raw_status() | self.take_rx_errors();
+        if let Some(error) = status.line_error() {
+            Err(nb::Error::Other(error))
+        } else if status.contains(Status::RX_FIFO_VALID) {
+            Ok(self.read_rx() as u8)
+        } else {
+            Err(nb::Error::WouldBlock)
+        }
+    }
+}
+
+/// ## embedded_hal_nb::serial::Write
+///
+/// Status error bits read while waiting are kept for the next read.
+#[cfg(feature = "embedded-hal-nb")]
+impl<'a> embedded_hal_nb::serial::Write for MmioUartAxiLite<'a> {
+    fn write(&mut self, word: u8) -> nb::Result<(), Self::Error> {
+        if self.tx_status().contains(Status::TX_FIFO_FULL) {
+            Err(nb::Error::WouldBlock)
+        } else {
+            self.write_byte(word);
+            Ok(())
+        }
+    }
+
+    fn flush(&mut self) -> nb::Result<(), Self::Error> {
+        if self.tx_status().contains(Status::TX_FIFO_EMPTY) {
+            Ok(())
+        } else {
+            Err(nb::Error::WouldBlock)
+        }
+    }
+}
+
+#[cfg(feature = "embedded-io")]
+impl<'a> embedded_io::ErrorType for MmioUartXpsLite<'a> {
+    type Error = LineError;
+}
+
+/// ## embedded_io::Read
+///
+/// Blocks until a character is received, then reads as many as are ready.
+/// A line error ends the read, and is returned by the next read if some bytes were already read.
+#[cfg(feature = "embedded-io")]
+impl<'a> embedded_io::Read for MmioUartXpsLite<'a> {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize, Self::Error> {
+        let mut count = 0;
+        while count < buf.len() {
+            let status = self.raw_status() | self.take_rx_errors();
+            if let Some(error) = status.line_error() {
+                if count == 0 {
+                    return Err(error);
+                }
+                self.keep_rx_errors(status);
+                break;
+            } else if status.contains(Status::RX_FIFO_VALID) {
+                buf[count] = self.read_rx().reverse_bits() as u8;
+                count += 1;
+            } else if count > 0 {
+                break;
+            } else {
+                core::hint::spin_loop();
+            }
+        }
+        Ok(count)
+    }
+}
+
+/// ## embedded_io::ReadReady
+#[cfg(feature = "embedded-io")]
+impl<'a> embedded_io::ReadReady for MmioUartXpsLite<'a> {
+    fn read_ready(&mut self) -> Result<bool, Self::Error> {
+        let status = self.raw_status() | self.take_rx_errors();
+        match status.line_error() {
+            Some(error) => Err(error),
+            None => Ok(status.contains(Status::RX_FIFO_VALID)),
+        }
+    }
+}
+
+/// ## embedded_io::Write
+///
+/// Blocks until the Tx FIFO has room, then fills it.
+/// Status error bits read while waiting are kept for the next read.
+#[cfg(feature = "embedded-io")]
+impl<'a> embedded_io::Write for MmioUartXpsLite<'a> {
+    fn write(&mut self, buf: &[u8]) -> Result<usize, Self::Error> {
+        let mut count = 0;
+        while count < buf.len() {
+            if !self.tx_status().contains(Status::TX_FIFO_FULL) {
+                self.write_byte(buf[count]);
+                count += 1;
+            } else if count > 0 {
+                break;
+            } else {
+                core::hint::spin_loop();
+            }
+        }
+        Ok(count)
+    }
+
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        while !self.tx_status().contains(Status::TX_FIFO_EMPTY) {
+            core::hint::spin_loop();
+        }
+        Ok(())
+    }
+}
+
+/// ## embedded_io::WriteReady
+#[cfg(feature = "embedded-io")]
+impl<'a> embedded_io::WriteReady for MmioUartXpsLite<'a> {
+    fn write_ready(&mut self) -> Result<bool, Self::Error> {
+        Ok(!self.tx_status().contains(Status::TX_FIFO_FULL))
+    }
+}
+
+#[cfg(feature = "embedded-io")]
+impl<'a> embedded_io::ErrorType for MmioUartAxiLite<'a> {
+    type Error = LineError;
+}
+
+/// ## embedded_io::Read
+///
+/// Blocks until a character is received, then reads as many as are ready.
+/// A line error ends the read, and is returned by the next read if some bytes were already read.
+#[cfg(feature = "embedded-io")]
+impl<'a> embedded_io::Read for MmioUartAxiLite<'a> {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize, Self::Error> {
+        let mut count = 0;
+        while count < buf.len() {
+            let status = self.raw_status() | self.take_rx_errors();
+            if let Some(error) = status.line_error() {
+                if count == 0 {
+                    return Err(error);
+                }
+                self.keep_rx_errors(status);
+                break;
+            } else if status.contains(Status::RX_FIFO_VALID) {
+                buf[count] = self.read_rx() as u8;
+                count += 1;
+            } else if count > 0 {
+                break;
+            } else {
+                core::hint::spin_loop();
+            }
+        }
+        Ok(count)
+    }
+}
+
+/// ## embedded_io::ReadReady
+#[cfg(feature = "embedded-io")]
+impl<'a> embedded_io::ReadReady for MmioUartAxiLite<'a> {
+    fn read_ready(&mut self) -> Result<bool, Self::Error> {
+        let status = self.raw_status() | self.take_rx_errors();
+        match status.line_error() {
+            Some(error) => Err(error),
+            None => Ok(status.contains(Status::RX_FIFO_VALID)),
+        }
+    }
+}
+
+/// ## embedded_io::Write
+///
+/// Blocks until the Tx FIFO has room, then fills it.
+/// Status error bits read while waiting are kept for the next read.
+#[cfg(feature = "embedded-io")]
+impl<'a> embedded_io::Write for MmioUartAxiLite<'a> {
+    fn write(&mut self, buf: &[u8]) -> Result<usize, Self::Error> {
+        let mut count = 0;
+        while count < buf.len() {
+            if !self.tx_status().contains(Status::TX_FIFO_FULL) {
+                self.write_byte(buf[count]);
+                count += 1;
+            } else if count > 0 {
+                break;
+            } else {
+                core::hint::spin_loop();
+            }
+        }
+        Ok(count)
+    }
+
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        while !self.tx_status().contains(Status::TX_FIFO_EMPTY) {
+            core::hint::spin_loop();
+        }
+        Ok(())
+    }
+}
+
+/// ## embedded_io::WriteReady
+#[cfg(feature = "embedded-io")]
+impl<'a> embedded_io::WriteReady for MmioUartAxiLite<'a> {
+    fn write_ready(&mut self) -> Result<bool, Self::Error> {
+        Ok(!self.tx_status().contains(Status::TX_FIFO_FULL))
+    }
+}
@@ -0,0 +1,108 @@
+use std::cell::Cell;
+use std::future::Future;
+use std::pin::pin;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::task::{Context, Poll, Wake, Waker};
+
+use uart_xilinx::uart_lite::{AsyncUartLite, Status};
+use uart_xilinx::{LineError, MmioUartAxiLite};
+
+/// RAM in the layout of the UART Lite registers
+///
+/// Unlike the hardware, reading `stat` doesn't clear its error bits, which the tests do by hand.
+#[derive(Default)]
+#[repr(C)]
+struct Registers {
+    rx: Cell<u32>,
+    tx: Cell<u32>,
+    stat: Cell<u32>,
+    ctrl: Cell<u32>,
+}
+
+impl Registers {
+    fn base_address(&self) -> usize {
+        self as *const Self as usize
+    }
+
+    fn set_status(&self, status: Status) {
+        self.stat.set(status.bits() as u32);
+    }
+}
+
+#[derive(Default)]
+struct CountingWaker(AtomicUsize);
+
+impl Wake for CountingWaker {
+    fn wake(self: Arc<Self>) {
+        self.0.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+#[test]
+fn read_waits_for_interrupt() {
+    let registers = Registers::default();
+    let uart = AsyncUartLite::new(unsafe { MmioUartAxiLite::new(registers.base_address()) });
+    uart.init();
+    assert_eq!(registers.ctrl.get(), 0b0001_0000);
+
+    let counter = Arc::new(CountingWaker::default());
+    let waker = Waker::from(counter.clone());
+    let mut cx = Context::from_waker(&waker);
+    let mut buf = [0; 4];
+    {
+        let mut read = pin!(uart.read(&mut buf));
+        assert!(read.as_mut().poll(&mut cx).is_pending());
+
+        registers.rx.set(b'a' as u32);
+        registers.set_status(Status::RX_FIFO_VALID);
+        uart.handle_interrupt();
+        assert_eq!(counter.0.load(Ordering::SeqCst), 1);
+
+        // The fake Rx FIFO never runs dry, so the whole buffer is filled
+        assert_eq!(read.as_mut().poll(&mut cx), Poll::Ready(Ok(4)));
+    }
+    assert_eq!(&buf, b"aaaa");
+}
+
+#[test]
+fn write_waits_for_room() {
+    let registers = Registers::default();
+    let uart = AsyncUartLite::new(unsafe { MmioUartAxiLite::new(registers.base_address()) });
+
+    let counter = Arc::new(CountingWaker::default());
+    let waker = Waker::from(counter.clone());
+    let mut cx = Context::from_waker(&waker);
+
+    registers.set_status(Status::TX_FIFO_FULL);
+    let mut write = pin!(uart.write(b"a"));
+    assert!(write.as_mut().poll(&mut cx).is_pending());
+    assert!(pin!(uart.flush()).poll(&mut cx).is_pending());
+
+    registers.set_status(Status::TX_FIFO_EMPTY);
+    uart.handle_interrupt();
+    assert_eq!(counter.0.load(Ordering::SeqCst), 1);
+    assert_eq!(write.as_mut().poll(&mut cx), Poll::Ready(Ok(1)));
+    assert_eq!(registers.tx.get(), b'a' as u32);
+    assert_eq!(pin!(uart.flush()).poll(&mut cx), Poll::Ready(Ok(())));
+}
+
+#[test]
+fn write_keeps_errors_for_read() {
+    let registers = Registers::default();
+    let uart = AsyncUartLite::new(unsafe { MmioUartAxiLite::new(registers.base_address()) });
+
+    let waker = Waker::from(Arc::new(CountingWaker::default()));
+    let mut cx = Context::from_waker(&waker);
+    let mut buf = [0; 4];
+
+    registers.set_status(Status::TX_FIFO_FULL | Status::OVERRUN_ERROR);
+    assert!(pin!(uart.write(b"a")).poll(&mut cx).is_pending());
+    registers.set_status(Status::TX_FIFO_EMPTY);
+    assert_eq!(
+        pin!(uart.read(&mut buf)).poll(&mut cx),
+        Poll::Ready(Err(LineError::Overrun))
+    );
+    assert!(pin!(uart.read(&mut buf)).poll(&mut cx).is_pending());
+    assert!(uart.with_uart(|uart| uart.line_errors().is_empty()));
+}
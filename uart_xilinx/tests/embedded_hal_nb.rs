@@ -0,0 +1,68 @@
+use std::cell::Cell;
+
+use embedded_hal_nb::nb;
+use embedded_hal_nb::serial::{Read, Write};
+use uart_xilinx::uart_lite::Status;
+use uart_xilinx::{LineError, MmioUartAxiLite, MmioUartXpsLite};
+
+/// RAM in the layout of the UART Lite registers
+///
+/// Unlike the hardware, reading `stat` doesn't clear its error bits, which the tests do by hand.
+#[derive(Default)]
+#[repr(C)]
+struct Registers {
+    rx: Cell<u32>,
+    tx: Cell<u32>,
+    stat: Cell<u32>,
+    ctrl: Cell<u32>,
+}
+
+impl Registers {
+    fn base_address(&self) -> usize {
+        self as *const Self as usize
+    }
+}
+
+#[test]
+fn write_keeps_errors_for_read() {
+    let registers = Registers::default();
+    let mut uart = unsafe { MmioUartAxiLite::new(registers.base_address()) };
+
+    registers.rx.set(b'a' as u32);
+    registers
+        .stat
+        .set((Status::RX_FIFO_VALID | Status::TX_FIFO_EMPTY | Status::PARITY_ERROR).bits() as u32);
+    assert_eq!(uart.write(b'x'), Ok(()));
+    assert_eq!(registers.tx.get(), b'x' as u32);
+
+    registers
+        .stat
+        .set((Status::RX_FIFO_VALID | Status::TX_FIFO_FULL).bits() as u32);
+    assert_eq!(uart.read(), Err(nb::Error::Other(LineError::Parity)));
+    assert_eq!(uart.read(), Ok(b'a'));
+    assert_eq!(uart.write(b'y'), Err(nb::Error::WouldBlock));
+    assert_eq!(uart.flush(), Err(nb::Error::WouldBlock));
+    assert!(uart.line_errors().is_empty());
+}
+
+#[test]
+fn xps_status_is_bit_reversed() {
+    let registers = Registers::default();
+    let mut uart = unsafe { MmioUartXpsLite::new(registers.base_address()) };
+
+    // The XPS UART Lite numbers its bits from the MSB
+    registers.rx.set((b'a' as u32).reverse_bits());
+    registers
+        .stat
+        .set(((Status::RX_FIFO_VALID | Status::FRAME_ERROR).bits() as u32).reverse_bits());
+    assert_eq!(uart.flush(), Err(nb::Error::WouldBlock));
+
+    registers
+        .stat
+        .set(((Status::RX_FIFO_VALID | Status::TX_FIFO_EMPTY).bits() as u32).reverse_bits());
+    assert_eq!(uart.read(), Err(nb::Error::Other(LineError::Framing)));
+    assert_eq!(uart.read(), Ok(b'a'));
+    assert_eq!(uart.flush(), Ok(()));
+    assert_eq!(uart.write(b'x'), Ok(()));
+    assert_eq!(registers.tx.get(), (b'x' as u32).reverse_bits());
+}
@@ -0,0 +1,53 @@
+use std::cell::Cell;
+
+use embedded_io::{Read, ReadReady, Write, WriteReady};
+use uart_xilinx::uart_lite::Status;
+use uart_xilinx::{LineError, MmioUartAxiLite};
+
+/// RAM in the layout of the UART Lite registers
+///
+/// Unlike the hardware, reading `stat` doesn't clear its error bits, which the tests do by hand.
+#[derive(Default)]
+#[repr(C)]
+struct Registers {
+    rx: Cell<u32>,
+    tx: Cell<u32>,
+    stat: Cell<u32>,
+    ctrl: Cell<u32>,
+}
+
+impl Registers {
+    fn base_address(&self) -> usize {
+        self as *const Self as usize
+    }
+
+    fn set_status(&self, status: Status) {
+        self.stat.set(status.bits() as u32);
+    }
+}
+
+#[test]
+fn write_keeps_errors_for_read() {
+    let registers = Registers::default();
+    let mut uart = unsafe { MmioUartAxiLite::new(registers.base_address()) };
+    let mut buf = [0; 8];
+
+    registers.rx.set(b'a' as u32);
+    registers.set_status(Status::TX_FIFO_EMPTY | Status::PARITY_ERROR);
+    assert_eq!(uart.write(b"x"), Ok(1));
+    registers.set_status(Status::TX_FIFO_EMPTY | Status::RX_FIFO_VALID);
+    assert_eq!(uart.read(&mut buf), Err(LineError::Parity));
+
+    registers.set_status(Status::TX_FIFO_EMPTY | Status::OVERRUN_ERROR);
+    uart.flush().unwrap();
+    registers.set_status(Status::TX_FIFO_EMPTY);
+    assert_eq!(uart.read_ready(), Err(LineError::Overrun));
+
+    registers.set_status(Status::FRAME_ERROR);
+    assert_eq!(uart.write_ready(), Ok(true));
+    registers.set_status(Status::TX_FIFO_FULL);
+    assert_eq!(uart.write_ready(), Ok(false));
+    assert_eq!(uart.read_ready(), Err(LineError::Framing));
+    assert_eq!(uart.read_ready(), Ok(false));
+    assert!(uart.line_errors().is_empty());
+}
@@ -0,0 +1,74 @@
+use std::cell::Cell;
+use std::thread;
+
+use uart_xilinx::uart_lite::Status;
+use uart_xilinx::{LineError, MmioUartAxiLite, MmioUartXpsLite};
+
+/// RAM in the layout of the UART Lite registers
+///
+/// Unlike the hardware, reading `stat` doesn't clear its error bits, which the tests do by hand.
+#[derive(Default)]
+#[repr(C)]
+struct Registers {
+    rx: Cell<u32>,
+    tx: Cell<u32>,
+    stat: Cell<u32>,
+    ctrl: Cell<u32>,
+}
+
+impl Registers {
+    fn base_address(&self) -> usize {
+        self as *const Self as usize
+    }
+}
+
+#[test]
+fn tx_keeps_errors_for_rx() {
+    let registers = Registers::default();
+    let mut uart = unsafe { MmioUartAxiLite::new(registers.base_address()) };
+    let (rx, tx) = uart.split();
+
+    registers.rx.set(b'a' as u32);
+    registers
+        .stat
+        .set((Status::RX_FIFO_VALID | Status::TX_FIFO_EMPTY | Status::FRAME_ERROR).bits() as u32);
+    assert!(tx.is_ready());
+    assert!(tx.is_idle());
+    tx.write_byte(b'x');
+    assert_eq!(registers.tx.get(), b'x' as u32);
+
+    registers
+        .stat
+        .set((Status::RX_FIFO_VALID | Status::TX_FIFO_FULL).bits() as u32);
+    assert!(!tx.is_ready());
+    assert!(rx.is_ready());
+    assert_eq!(rx.read_byte(), Err(LineError::Framing));
+    assert_eq!(rx.read_byte(), Ok(Some(b'a')));
+
+    registers.stat.set(0);
+    assert_eq!(rx.read_byte(), Ok(None));
+    // The halves never touch the interrupt enable
+    assert_eq!(registers.ctrl.get(), 0);
+}
+
+#[test]
+fn halves_can_be_sent() {
+    let registers = Registers::default();
+    // The XPS UART Lite numbers its bits from the MSB
+    registers.rx.set((b'a' as u32).reverse_bits());
+    registers
+        .stat
+        .set(((Status::RX_FIFO_VALID | Status::TX_FIFO_EMPTY).bits() as u32).reverse_bits());
+    let base_address = registers.base_address();
+
+    let mut uart = unsafe { MmioUartXpsLite::new(base_address) };
+    let (rx, tx) = uart.split();
+    thread::scope(|scope| {
+        scope.spawn(move || assert_eq!(rx.read_byte(), Ok(Some(b'a'))));
+        scope.spawn(move || {
+            assert!(tx.is_ready());
+            tx.write_byte(b'x');
+        });
+    });
+    assert_eq!(registers.tx.get(), (b'x' as u32).reverse_bits());
+}
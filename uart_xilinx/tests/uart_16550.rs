@@ -0,0 +1,38 @@
+use std::cell::Cell;
+
+use uart_xilinx::uart_16550::{IER, LSR};
+use uart_xilinx::{Axi16550Layout, MmioUartAxi16550};
+
+/// RAM in the layout of the AXI UART 16550 registers, 32 bits each
+///
+/// DLL and DLH share RAM with RBR/THR and IER, as nothing here switches on DLAB.
+#[derive(Default)]
+struct Registers([Cell<u32>; 8]);
+
+impl Registers {
+    fn base_address(&self) -> usize {
+        self.0.as_ptr() as usize
+    }
+}
+
+#[test]
+fn registers_are_32_bits_4_bytes_apart() {
+    let registers = Registers::default();
+    let uart = unsafe { MmioUartAxi16550::with_layout(registers.base_address(), Axi16550Layout) };
+
+    uart.write_thr(b'a');
+    assert_eq!(registers.0[0].get(), b'a' as u32);
+    uart.set_ier(IER::RDAI);
+    assert_eq!(registers.0[1].get(), IER::RDAI.bits() as u32);
+    uart.write_lcr(0b0000_0011);
+    assert_eq!(registers.0[3].get(), 0b0000_0011);
+    uart.write_sr(0x5a);
+    assert_eq!(registers.0[7].get(), 0x5a);
+
+    // Only the low byte of each register is used
+    registers.0[0].set(0xffff_ff00 | b'b' as u32);
+    assert_eq!(uart.read_rbr(), b'b');
+    registers.0[5].set(0xffff_ff00 | (LSR::DR | LSR::THRE).bits() as u32);
+    assert_eq!(uart.lsr(), LSR::DR | LSR::THRE);
+    assert_eq!(uart.read_byte(), Some(b'b'));
+}
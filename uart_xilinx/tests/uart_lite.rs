@@ -0,0 +1,88 @@
+use std::cell::Cell;
+
+use uart_xilinx::uart_lite::Status;
+use uart_xilinx::{MmioUartAxiLite, MmioUartXpsLite};
+
+/// RAM in the layout of the UART Lite registers
+///
+/// Unlike the hardware, reading `stat` doesn't clear its error bits, which the tests do by hand.
+#[derive(Default)]
+#[repr(C)]
+struct Registers {
+    rx: Cell<u32>,
+    tx: Cell<u32>,
+    stat: Cell<u32>,
+    ctrl: Cell<u32>,
+}
+
+impl Registers {
+    fn base_address(&self) -> usize {
+        self as *const Self as usize
+    }
+}
+
+#[test]
+fn interrupt_enable() {
+    let registers = Registers::default();
+    let uart = unsafe { MmioUartAxiLite::new(registers.base_address()) };
+    uart.enable_interrupt();
+    assert_eq!(registers.ctrl.get(), 0b1_0000);
+    uart.disable_interrupt();
+    assert_eq!(registers.ctrl.get(), 0);
+
+    // The XPS UART Lite numbers its bits from the MSB
+    let registers = Registers::default();
+    let uart = unsafe { MmioUartXpsLite::new(registers.base_address()) };
+    uart.enable_interrupt();
+    assert_eq!(registers.ctrl.get(), 0b1_0000_u32.reverse_bits());
+    uart.disable_interrupt();
+    assert_eq!(registers.ctrl.get(), 0);
+}
+
+#[test]
+fn status_collects_errors() {
+    let registers = Registers::default();
+    let uart = unsafe { MmioUartAxiLite::new(registers.base_address()) };
+
+    registers
+        .stat
+        .set((Status::RX_FIFO_VALID | Status::PARITY_ERROR).bits() as u32);
+    // Checking another bit doesn't lose the error
+    assert!(uart.is_rx_fifo_valid());
+    registers.stat.set(Status::OVERRUN_ERROR.bits() as u32);
+    assert!(uart.is_overrun_error());
+    registers.stat.set(0);
+    assert!(!uart.is_parity_error());
+
+    assert_eq!(
+        uart.line_errors(),
+        Status::PARITY_ERROR | Status::OVERRUN_ERROR
+    );
+    assert_eq!(
+        uart.take_line_errors(),
+        Status::PARITY_ERROR | Status::OVERRUN_ERROR
+    );
+    assert!(uart.take_line_errors().is_empty());
+
+    // Only the error bits are collected
+    registers
+        .stat
+        .set((Status::TX_FIFO_EMPTY | Status::INTERRUPT_ENABLED).bits() as u32);
+    assert_eq!(
+        uart.status(),
+        Status::TX_FIFO_EMPTY | Status::INTERRUPT_ENABLED
+    );
+    assert!(uart.line_errors().is_empty());
+}
+
+#[test]
+fn xps_status_collects_errors() {
+    let registers = Registers::default();
+    let uart = unsafe { MmioUartXpsLite::new(registers.base_address()) };
+
+    registers
+        .stat
+        .set(((Status::TX_FIFO_FULL | Status::FRAME_ERROR).bits() as u32).reverse_bits());
+    assert_eq!(uart.status(), Status::TX_FIFO_FULL | Status::FRAME_ERROR);
+    assert_eq!(uart.take_line_errors(), Status::FRAME_ERROR);
+}